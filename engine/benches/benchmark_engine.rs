@@ -1,21 +1,53 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use engine::{Board, Point, Snake};
+use engine::{safe_move, Board, Point, Snake, SnakeGame};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const SEED: u64 = 42;
 
 fn benchmark_n_snakes(c: &mut Criterion, snakes: Vec<Snake>) {
     let n_snakes = snakes.len();
 
     c.bench_function(&format!("Engine_board_21x21_{}_snakes", n_snakes), |b| {
-        let mut board = Board::new(21, 21, snakes.clone());
+        let mut board = Board::new(21, 21, snakes.clone()).with_seed(SEED);
+        let mut rng = StdRng::seed_from_u64(SEED);
         b.iter(|| {
             for _ in 0..15 {
                 if board.alive_snakes().count() > 0 {
-                    board.step((0..n_snakes).into_iter().map(|_| rand::random()).collect());
+                    board.step((0..n_snakes).map(|_| rng.gen()).collect(), false);
                 }
             }
         })
     });
 }
 
+/// Moves of the snakes of a game some turns in, where the flood fills run into bodies.
+fn benchmark_moves(c: &mut Criterion, snakes: Vec<Snake>) {
+    let n_snakes = snakes.len();
+    let mut game = SnakeGame::new(Board::new(21, 21, snakes).with_seed(SEED));
+    for _ in 0..20 {
+        game.step((0..n_snakes).map(|id| safe_move(&game, id)).collect());
+    }
+
+    c.bench_function(&format!("Moves_board_21x21_{}_snakes", n_snakes), |b| {
+        b.iter(|| {
+            (0..n_snakes)
+                .filter(|&id| !game.board().snakes()[id].is_dead())
+                .map(|id| (game.non_fatal_moves(id), safe_move(&game, id)))
+                .collect::<Vec<_>>()
+        })
+    });
+
+    // the evaluation of the positions, behind each playout
+    #[cfg(feature = "search")]
+    c.bench_function(&format!("Playouts_board_21x21_{}_snakes", n_snakes), |b| {
+        b.iter(|| {
+            let mut duct = engine::Duct::new(game.clone(), 0.7);
+            duct.playout_n(100);
+            duct.visits()
+        })
+    });
+}
+
 pub fn benchmark_engine_10_snakes(c: &mut Criterion) {
     let snakes = [
         Point { x: 2, y: 4 },
@@ -31,9 +63,10 @@ pub fn benchmark_engine_10_snakes(c: &mut Criterion) {
     ]
     .iter()
     .map(|&p| Snake::new(p))
-    .collect();
+    .collect::<Vec<Snake>>();
 
-    benchmark_n_snakes(c, snakes);
+    benchmark_n_snakes(c, snakes.clone());
+    benchmark_moves(c, snakes);
 }
 
 pub fn benchmark_engine_4_snakes(c: &mut Criterion) {
@@ -45,9 +78,10 @@ pub fn benchmark_engine_4_snakes(c: &mut Criterion) {
     ]
     .iter()
     .map(|&p| Snake::new(p))
-    .collect();
+    .collect::<Vec<Snake>>();
 
-    benchmark_n_snakes(c, snakes);
+    benchmark_n_snakes(c, snakes.clone());
+    benchmark_moves(c, snakes);
 }
 
 criterion_group!(
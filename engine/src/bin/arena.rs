@@ -0,0 +1,78 @@
+//! Plays games between bots without rendering them and prints the results.
+//!
+//! ```text
+//! arena [--games N] [--width W] [--height H] [--ruleset NAME] [--think-ms MS]
+//!       [--max-turns N] [--seed S] PLAYER...
+//! ```
+//!
+//! Players are bot configurations like `random` or `bot_a:threads=4`, see `BotConfig`.
+//! `bot_a random` by default.
+
+mod common;
+
+use engine::{BotConfig, Outcome};
+
+use common::{Args, GameOptions};
+
+const USAGE: &str = "usage: arena [--games N] [--width W] [--height H] [--ruleset NAME] \
+                     [--think-ms MS] [--max-turns N] [--seed S] PLAYER...";
+
+struct Options {
+    games: usize,
+    game: GameOptions,
+    players: Vec<BotConfig>,
+}
+
+fn parse_options(args: &mut Args) -> Options {
+    let mut options = Options {
+        games: 10,
+        game: GameOptions::default(),
+        players: vec![],
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--games" => options.games = args.value(&arg),
+            _ if options.game.parse(&arg, args) => {}
+            "-h" | "--help" => args.exit_with_help(),
+            _ if arg.starts_with('-') => args.exit_with_usage(&format!("unknown option {}", arg)),
+            _ => options.players.push(
+                arg.parse()
+                    .unwrap_or_else(|e: String| args.exit_with_usage(&e)),
+            ),
+        }
+    }
+
+    if options.players.is_empty() {
+        options.players = vec!["bot_a".parse().unwrap(), "random".parse().unwrap()];
+    }
+
+    options
+}
+
+fn main() {
+    let mut args = Args::new(USAGE);
+    let options = parse_options(&mut args);
+
+    let mut arena = options
+        .game
+        .arena()
+        .unwrap_or_else(|e| args.exit_with_usage(&e));
+
+    // the same bot may play several snakes, they are told apart by their position
+    for (i, config) in options.players.iter().enumerate() {
+        arena = arena.with_player(&format!("{}#{}", config, i), config.factory());
+    }
+
+    let names: Vec<String> = arena.player_names().map(String::from).collect();
+    let stats = arena.run(options.games, |i, result| {
+        let outcome = match result.outcome {
+            Outcome::Win { id } => format!("won by {}", names[id]),
+            _ => "draw".to_string(),
+        };
+        println!("game {:>4}: {} in {} turns", i + 1, outcome, result.turns);
+    });
+
+    println!();
+    print!("{}", stats);
+}
@@ -0,0 +1,103 @@
+//! Command line parsing shared by the `arena` and `tournament` binaries, which both set up the
+//! games the bots play.
+
+use std::env;
+use std::iter::Skip;
+use std::process;
+use std::str::FromStr;
+use std::time::Duration;
+
+use engine::{ruleset_from_name, Arena};
+
+/// Arguments of the binary, reported along with its usage when they are wrong.
+pub struct Args {
+    usage: &'static str,
+    args: Skip<env::Args>,
+}
+
+impl Args {
+    pub fn new(usage: &'static str) -> Self {
+        Args {
+            usage,
+            args: env::args().skip(1),
+        }
+    }
+
+    pub fn next(&mut self) -> Option<String> {
+        self.args.next()
+    }
+
+    /// Parses the argument following the option `name`.
+    pub fn value<T: FromStr>(&mut self, name: &str) -> T {
+        let value = self.args.next().and_then(|v| v.parse().ok());
+        value.unwrap_or_else(|| self.exit_with_usage(&format!("invalid value for {}", name)))
+    }
+
+    pub fn exit_with_help(&self) -> ! {
+        println!("{}", self.usage);
+        process::exit(0);
+    }
+
+    pub fn exit_with_usage(&self, error: &str) -> ! {
+        eprintln!("{}\n{}", error, self.usage);
+        process::exit(1);
+    }
+}
+
+/// Board and rules of the games:
+/// `[--width W] [--height H] [--ruleset NAME] [--think-ms MS] [--max-turns N] [--seed S]`.
+#[derive(Clone)]
+pub struct GameOptions {
+    width: i32,
+    height: i32,
+    ruleset: String,
+    think_ms: u64,
+    max_turns: u32,
+    seed: Option<u64>,
+}
+
+impl Default for GameOptions {
+    fn default() -> Self {
+        GameOptions {
+            width: 11,
+            height: 11,
+            ruleset: "standard".to_string(),
+            think_ms: 100,
+            max_turns: 1000,
+            seed: None,
+        }
+    }
+}
+
+impl GameOptions {
+    /// Reads the value of `arg` if it is one of the game options, returns false otherwise.
+    pub fn parse(&mut self, arg: &str, args: &mut Args) -> bool {
+        match arg {
+            "--width" => self.width = args.value(arg),
+            "--height" => self.height = args.value(arg),
+            "--ruleset" => self.ruleset = args.value(arg),
+            "--think-ms" => self.think_ms = args.value(arg),
+            "--max-turns" => self.max_turns = args.value(arg),
+            "--seed" => self.seed = Some(args.value(arg)),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Arena without players, fails on an unknown ruleset or an unsupported size.
+    pub fn arena(&self) -> Result<Arena, String> {
+        let ruleset = ruleset_from_name(&self.ruleset);
+        if ruleset.name() != self.ruleset {
+            return Err(format!("unknown ruleset {}", self.ruleset));
+        }
+
+        let arena = Arena::new(self.width, self.height)?
+            .with_ruleset(ruleset)
+            .with_think_time(Duration::from_millis(self.think_ms))
+            .with_max_turns(self.max_turns);
+        Ok(match self.seed {
+            Some(seed) => arena.with_seed(seed),
+            None => arena,
+        })
+    }
+}
@@ -0,0 +1,107 @@
+//! Plays a tournament between bot configurations and updates a leaderboard file.
+//!
+//! ```text
+//! tournament [--format round-robin|swiss] [--rounds N] [--games N] [--leaderboard FILE]
+//!            [--width W] [--height H] [--ruleset NAME] [--think-ms MS] [--max-turns N]
+//!            [--seed S] BOT BOT...
+//! ```
+//!
+//! Bots are configurations like `random` or `bot_a:threads=4`, see `BotConfig`. Ratings carry
+//! over from a run to another through the leaderboard, `leaderboard.json` by default.
+
+mod common;
+
+use engine::{duplicate_bot, BotConfig, Format, Leaderboard, Tournament};
+
+use common::{Args, GameOptions};
+
+const USAGE: &str = "usage: tournament [--format round-robin|swiss] [--rounds N] [--games N] \
+                     [--leaderboard FILE] [--width W] [--height H] [--ruleset NAME] \
+                     [--think-ms MS] [--max-turns N] [--seed S] BOT BOT...";
+
+struct Options {
+    format: String,
+    rounds: Option<usize>,
+    games: usize,
+    leaderboard: String,
+    game: GameOptions,
+    bots: Vec<BotConfig>,
+}
+
+fn parse_options(args: &mut Args) -> Options {
+    let mut options = Options {
+        format: "round-robin".to_string(),
+        rounds: None,
+        games: 2,
+        leaderboard: "leaderboard.json".to_string(),
+        game: GameOptions::default(),
+        bots: vec![],
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => options.format = args.value(&arg),
+            "--rounds" => options.rounds = Some(args.value(&arg)),
+            "--games" => options.games = args.value(&arg),
+            "--leaderboard" => options.leaderboard = args.value(&arg),
+            _ if options.game.parse(&arg, args) => {}
+            "-h" | "--help" => args.exit_with_help(),
+            _ if arg.starts_with('-') => args.exit_with_usage(&format!("unknown option {}", arg)),
+            _ => options.bots.push(
+                arg.parse()
+                    .unwrap_or_else(|e: String| args.exit_with_usage(&e)),
+            ),
+        }
+    }
+
+    if options.bots.len() < 2 {
+        args.exit_with_usage("at least two bots are needed");
+    }
+    if let Some(bot) = duplicate_bot(&options.bots) {
+        args.exit_with_usage(&format!("{} is entered twice", bot));
+    }
+
+    options
+}
+
+fn main() {
+    let mut args = Args::new(USAGE);
+    let options = parse_options(&mut args);
+
+    let format = match options.format.as_str() {
+        "round-robin" => Format::RoundRobin,
+        // enough rounds to tell the best bot apart
+        "swiss" => Format::Swiss {
+            rounds: options
+                .rounds
+                .unwrap_or_else(|| (options.bots.len() as f64).log2().ceil().max(1.) as usize),
+        },
+        _ => args.exit_with_usage(&format!("unknown format {}", options.format)),
+    };
+
+    if let Err(e) = options.game.arena() {
+        args.exit_with_usage(&e);
+    }
+    let game = options.game;
+    let new_arena = Box::new(move || game.arena().expect("the options are checked above"));
+
+    let mut leaderboard =
+        Leaderboard::load(&options.leaderboard).expect("can't read the leaderboard file");
+    let tournament =
+        Tournament::new(options.bots, format, new_arena).with_games_per_match(options.games);
+
+    let bots: Vec<String> = tournament.bots().iter().map(|b| b.to_string()).collect();
+    tournament.run(&mut leaderboard, |result| {
+        println!(
+            "{} vs {}: {}-{} ({} draws)",
+            bots[result.a], bots[result.b], result.wins_a, result.wins_b, result.draws
+        );
+    });
+
+    leaderboard
+        .save(&options.leaderboard)
+        .expect("can't write the leaderboard file");
+
+    println!();
+    print!("{}", leaderboard);
+}
@@ -0,0 +1,136 @@
+//! Watches games in the terminal: `tui` plays bots against each other and `tui <file>` plays
+//! back a recorded game.
+//!
+//! Space pauses, Right and Left step forward and back, Q or Escape quits.
+
+use engine::renderer::{board_dimension, render_board, render_status};
+use engine::{Board, BotA, Player, Point, Replay, Snake, SnakeGame};
+use ruscii::app::{App, State};
+use ruscii::drawing::Pencil;
+use ruscii::keyboard::{Key, KeyEvent};
+use ruscii::spatial::Vec2;
+use ruscii::terminal::Window;
+
+const BOARD_WIDTH: i32 = 11;
+const BOARD_HEIGHT: i32 = 11;
+const N_THREADS: usize = 2;
+const FREQ_SECONDS: f64 = 0.3;
+
+enum Source {
+    Live {
+        game: Box<SnakeGame>,
+        players: Vec<Box<dyn Player>>,
+    },
+    Replay {
+        replay: Replay,
+        turn: usize,
+    },
+}
+
+impl Source {
+    fn live() -> Self {
+        let snakes = vec![
+            Snake::new(Point { x: 1, y: 1 }),
+            Snake::new(Point { x: 9, y: 9 }),
+        ];
+        let game = Box::new(SnakeGame::new(Board::new(
+            BOARD_WIDTH,
+            BOARD_HEIGHT,
+            snakes,
+        )));
+
+        let mut players: Vec<Box<dyn Player>> = (0..2)
+            .map(|id| Box::new(BotA::new(id, N_THREADS, [0.; 4])) as Box<dyn Player>)
+            .collect();
+        players.iter_mut().for_each(|p| p.think(&game));
+
+        Source::Live { game, players }
+    }
+
+    fn board(&self) -> &Board {
+        match self {
+            Source::Live { game, .. } => game.board(),
+            Source::Replay { replay, turn } => &replay.frames()[*turn].board,
+        }
+    }
+
+    /// Returns false when the game is already over.
+    fn next(&mut self) -> bool {
+        match self {
+            Source::Live { game, players } => {
+                if game.board().is_game_over() {
+                    return false;
+                }
+
+                let moves = players.iter_mut().map(|p| p.next_move()).collect();
+                game.step(moves);
+
+                players.iter_mut().enumerate().for_each(|(id, p)| {
+                    game.set_player(id);
+                    p.think(game);
+                });
+            }
+            Source::Replay { replay, turn } => {
+                if *turn == replay.nb_turns() {
+                    return false;
+                }
+                *turn += 1;
+            }
+        }
+
+        true
+    }
+
+    /// Live games can't go back.
+    fn previous(&mut self) {
+        if let Source::Replay { turn, .. } = self {
+            *turn = turn.saturating_sub(1);
+        }
+    }
+}
+
+fn main() {
+    let mut source = match std::env::args().nth(1) {
+        Some(path) => Source::Replay {
+            replay: Replay::load(path).expect("can't read the replay file"),
+            turn: 0,
+        },
+        None => Source::live(),
+    };
+
+    let mut paused = false;
+    let mut time = 0.;
+
+    App::new().run(|state: &mut State, window: &mut Window| {
+        for key_event in state.keyboard().last_key_events() {
+            match key_event {
+                KeyEvent::Pressed(Key::Esc) | KeyEvent::Pressed(Key::Q) => state.stop(),
+                KeyEvent::Pressed(Key::Space) => paused = !paused,
+                KeyEvent::Pressed(Key::Right) => {
+                    paused = true;
+                    source.next();
+                }
+                KeyEvent::Pressed(Key::Left) => {
+                    paused = true;
+                    source.previous();
+                }
+                _ => (),
+            }
+        }
+
+        if !paused {
+            time += state.dt().as_secs_f64();
+            if time >= FREQ_SECONDS {
+                time = 0.;
+                paused = !source.next();
+            }
+        }
+
+        let board = source.board();
+        let mut pencil = Pencil::new(window.canvas_mut());
+        pencil.set_origin(Vec2::xy(2, 1));
+        render_board(&mut pencil, board);
+        pencil.set_origin(Vec2::xy(2 + board_dimension(board).x + 4, 1));
+        render_status(&mut pencil, board);
+    });
+}
@@ -0,0 +1,381 @@
+//! Payloads of the official Battlesnake API, as sent to `/start`, `/move` and `/end`.
+//!
+//! A turn converted to the engine and back is given back unchanged, from its `Game` and the
+//! `SnakeInfo` of its snakes which hold what the engine doesn't keep. Snakes dying in the
+//! engine are left out of the board, as the API only lists the snakes still playing.
+
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::bitboard::check_size;
+use crate::engine::{self, ruleset_from_name, Point};
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct Turn {
+    pub game: Game,
+    pub turn: u32,
+    pub board: Board,
+    pub you: Snake,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct Game {
+    pub id: String,
+    pub ruleset: Ruleset,
+    pub timeout: i32,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct Ruleset {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub settings: Settings,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    /// Percentage of chance for a food to spawn each turn.
+    pub food_spawn_chance: u32,
+    pub minimum_food: u32,
+    pub hazard_damage_per_turn: i32,
+    pub royale: RoyaleSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            food_spawn_chance: 15,
+            minimum_food: 1,
+            hazard_damage_per_turn: 14,
+            royale: RoyaleSettings::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RoyaleSettings {
+    pub shrink_every_n_turns: u32,
+}
+
+impl Default for RoyaleSettings {
+    fn default() -> Self {
+        RoyaleSettings {
+            shrink_every_n_turns: 25,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct Board {
+    pub height: i32,
+    pub width: i32,
+    pub food: Vec<Point>,
+    pub snakes: Vec<Snake>,
+    pub hazards: Vec<Point>,
+}
+
+/// Body goes from the head to the tail, segments eaten this turn are stacked on the tail.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct Snake {
+    pub id: String,
+    pub name: String,
+    pub health: i32,
+    pub body: Vec<Point>,
+    pub head: Point,
+    pub length: u32,
+    #[serde(default)]
+    pub shout: String,
+    #[serde(default)]
+    pub squad: String,
+    #[serde(default)]
+    pub latency: String,
+}
+
+/// What the API tells of a snake and the engine doesn't keep.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct SnakeInfo {
+    pub id: String,
+    pub name: String,
+    pub shout: String,
+    pub squad: String,
+    pub latency: String,
+}
+
+impl SnakeInfo {
+    /// Snakes of the engine don't have a name, `id` is used instead.
+    pub fn new(id: &str) -> Self {
+        SnakeInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            ..SnakeInfo::default()
+        }
+    }
+}
+
+impl From<&Snake> for SnakeInfo {
+    fn from(s: &Snake) -> Self {
+        SnakeInfo {
+            id: s.id.clone(),
+            name: s.name.clone(),
+            shout: s.shout.clone(),
+            squad: s.squad.clone(),
+            latency: s.latency.clone(),
+        }
+    }
+}
+
+// Api to engine
+impl From<&Settings> for engine::Settings {
+    fn from(settings: &Settings) -> Self {
+        engine::Settings {
+            food_spawn_chance: settings.food_spawn_chance as f32 / 100.,
+            minimum_food: settings.minimum_food as usize,
+            hazard_damage_per_turn: settings.hazard_damage_per_turn,
+            shrink_every_n_turns: settings.royale.shrink_every_n_turns,
+        }
+    }
+}
+
+impl From<&Snake> for engine::Snake {
+    fn from(s: &Snake) -> Self {
+        engine::Snake::new_from(
+            s.health,
+            s.body.iter().rev().copied().collect(),
+            s.length as usize,
+            s.head,
+        )
+    }
+}
+
+/// Snakes keep the order of `board.snakes`, which gives their ids in the engine. Boards too
+/// large for the engine are rejected.
+impl TryFrom<&Turn> for engine::Board {
+    type Error = String;
+
+    fn try_from(turn: &Turn) -> Result<Self, Self::Error> {
+        let board = &turn.board;
+        check_size(board.width, board.height)?;
+
+        Ok(engine::Board::new_from(
+            board.width,
+            board.height,
+            board.snakes.iter().map(engine::Snake::from).collect(),
+            &board.food,
+            &board.hazards,
+        )
+        .with_ruleset(ruleset_from_name(&turn.game.ruleset.name))
+        .with_settings(engine::Settings::from(&turn.game.ruleset.settings))
+        .with_turn(turn.turn))
+    }
+}
+
+// Engine to api
+impl From<&engine::Settings> for Settings {
+    fn from(settings: &engine::Settings) -> Self {
+        Settings {
+            food_spawn_chance: (settings.food_spawn_chance * 100.).round() as u32,
+            minimum_food: settings.minimum_food as u32,
+            hazard_damage_per_turn: settings.hazard_damage_per_turn,
+            royale: RoyaleSettings {
+                shrink_every_n_turns: settings.shrink_every_n_turns,
+            },
+        }
+    }
+}
+
+impl Snake {
+    pub fn from_engine(info: &SnakeInfo, snake: &engine::Snake) -> Self {
+        let mut body: VecDeque<Point> = snake.body().iter().rev().copied().collect();
+        if let Some(&tail) = body.back() {
+            body.resize(snake.length(), tail);
+        }
+
+        Snake {
+            id: info.id.clone(),
+            name: info.name.clone(),
+            health: snake.health(),
+            body: body.into_iter().collect(),
+            head: *snake.head(),
+            length: snake.length() as u32,
+            shout: info.shout.clone(),
+            squad: info.squad.clone(),
+            latency: info.latency.clone(),
+        }
+    }
+}
+
+impl Board {
+    /// `snakes` has one entry per snake of the engine, dead snakes are left out.
+    pub fn from_engine(board: &engine::Board, snakes: &[SnakeInfo]) -> Self {
+        debug_assert_eq!(snakes.len(), board.snakes().len());
+
+        let mut food: Vec<Point> = board.food().iter().copied().collect();
+        food.sort_unstable_by_key(|p| (p.x, p.y));
+        let mut hazards: Vec<Point> = board.hazards().iter().copied().collect();
+        hazards.sort_unstable_by_key(|p| (p.x, p.y));
+
+        Board {
+            height: board.height(),
+            width: board.width(),
+            food,
+            snakes: board
+                .alive_snakes()
+                .map(|(id, s)| Snake::from_engine(&snakes[id], s))
+                .collect(),
+            hazards,
+        }
+    }
+}
+
+impl Game {
+    /// Game played with the rules of `board`, from a ruleset without version.
+    pub fn from_engine(id: &str, timeout: i32, board: &engine::Board) -> Self {
+        Game {
+            id: id.to_string(),
+            ruleset: Ruleset {
+                name: board.ruleset().name().to_string(),
+                version: String::new(),
+                settings: Settings::from(board.settings()),
+            },
+            timeout,
+        }
+    }
+}
+
+impl Turn {
+    /// Payload of the `/move` request of `game` sent to the snake `you` for the current state
+    /// of `board`.
+    pub fn from_engine(
+        game: &Game,
+        board: &engine::Board,
+        snakes: &[SnakeInfo],
+        you: usize,
+    ) -> Self {
+        Turn {
+            game: game.clone(),
+            turn: board.turn(),
+            board: Board::from_engine(board, snakes),
+            you: Snake::from_engine(&snakes[you], &board.snakes()[you]),
+        }
+    }
+
+    /// What the engine doesn't keep of the snakes, by their id in the engine.
+    pub fn snake_infos(&self) -> Vec<SnakeInfo> {
+        self.board.snakes.iter().map(SnakeInfo::from).collect()
+    }
+
+    /// Id of `you` in the engine, none when it isn't on the board.
+    pub fn you_id(&self) -> Option<usize> {
+        self.board.snakes.iter().position(|s| s.id == self.you.id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::Movement;
+
+    fn snake(id: &str, body: &[(i32, i32)], health: i32, squad: &str, latency: &str) -> String {
+        let body: Vec<String> = body
+            .iter()
+            .map(|(x, y)| format!(r#"{{"x": {}, "y": {}}}"#, x, y))
+            .collect();
+        format!(
+            r#"{{
+                "id": "snake-{id}", "name": "Snake {id}", "health": {health},
+                "body": [{body}], "head": {head}, "length": {length},
+                "shout": "{id}!", "squad": "{squad}", "latency": "{latency}"
+            }}"#,
+            id = id,
+            health = health,
+            head = body[0],
+            body = body.join(", "),
+            length = body.len(),
+            squad = squad,
+            latency = latency,
+        )
+    }
+
+    fn turn() -> Turn {
+        let a = snake("a", &[(0, 0), (1, 0), (2, 0), (2, 0)], 54, "1", "111");
+        let b = snake("b", &[(5, 5), (5, 4), (5, 3)], 90, "2", "42");
+        let c = snake("c", &[(9, 9), (9, 8), (9, 7)], 3, "1", "0");
+        serde_json::from_str(&format!(
+            r#"{{
+                "game": {{
+                    "id": "game-00fe20da-94ad-11ea-bb37",
+                    "ruleset": {{
+                        "name": "royale",
+                        "version": "v1.2.3",
+                        "settings": {{
+                            "foodSpawnChance": 25,
+                            "minimumFood": 2,
+                            "hazardDamagePerTurn": 16,
+                            "royale": {{"shrinkEveryNTurns": 10}}
+                        }}
+                    }},
+                    "timeout": 500
+                }},
+                "turn": 14,
+                "board": {{
+                    "height": 11,
+                    "width": 11,
+                    "food": [{{"x": 2, "y": 6}}, {{"x": 5, "y": 6}}],
+                    "hazards": [{{"x": 3, "y": 2}}],
+                    "snakes": [{a}, {b}, {c}]
+                }},
+                "you": {b}
+            }}"#,
+            a = a,
+            b = b,
+            c = c
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn turn_to_engine_and_back() {
+        let turn = turn();
+        let board = engine::Board::try_from(&turn).unwrap();
+        assert_eq!(board.ruleset().name(), "royale");
+        assert_eq!(board.snakes()[2].health(), 3);
+
+        let you = turn.you_id().unwrap();
+        assert_eq!(you, 1);
+        let snakes = turn.snake_infos();
+        assert_eq!(Turn::from_engine(&turn.game, &board, &snakes, you), turn);
+
+        // a hits the wall and b eats
+        let mut board = board;
+        board.step(vec![Movement::Left, Movement::Up, Movement::Up], true);
+        let next = Turn::from_engine(&turn.game, &board, &snakes, you);
+
+        assert_eq!(next.turn, 15);
+        assert_eq!(next.board.food, vec![Point { x: 2, y: 6 }]);
+        let ids: Vec<&str> = next.board.snakes.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["snake-b", "snake-c"]);
+        assert_eq!(next.board.snakes[0], next.you);
+        assert_eq!(next.you.head, Point { x: 5, y: 6 });
+        assert_eq!(next.you.health, 100);
+        assert_eq!(next.you.length, 4);
+        assert_eq!(next.you.body.len(), 4);
+        assert_eq!(SnakeInfo::from(&next.you), snakes[1]);
+        assert_eq!(SnakeInfo::from(&next.board.snakes[1]), snakes[2]);
+    }
+
+    #[test]
+    fn boards_too_large_are_rejected() {
+        let mut turn = turn();
+        turn.board.width = 26;
+        turn.board.height = 25;
+        assert!(engine::Board::try_from(&turn).is_err());
+
+        turn.board.width = 25;
+        assert!(engine::Board::try_from(&turn).is_ok());
+    }
+}
@@ -0,0 +1,393 @@
+//! Headless matches between `Player`s, to compare bots without watching them.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::thread;
+use std::time::Duration;
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use super::bitboard::check_size;
+use super::{
+    ruleset::Standard, Board, Collision, Outcome, Player, Point, Ruleset, Settings, Snake,
+    SnakeGame,
+};
+
+/// Builds the player controlling the snake of the given id, a new one for every game.
+pub type PlayerFactory = Box<dyn Fn(usize) -> Box<dyn Player>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeathCause {
+    Wall,
+    SelfBody,
+    OtherBody,
+    HeadToHead,
+    Starvation,
+    /// Starved while standing in a hazard.
+    Hazard,
+}
+
+impl DeathCause {
+    pub const ALL: [DeathCause; 6] = [
+        DeathCause::Wall,
+        DeathCause::SelfBody,
+        DeathCause::OtherBody,
+        DeathCause::HeadToHead,
+        DeathCause::Starvation,
+        DeathCause::Hazard,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeathCause::Wall => "wall",
+            DeathCause::SelfBody => "self",
+            DeathCause::OtherBody => "body",
+            DeathCause::HeadToHead => "head",
+            DeathCause::Starvation => "starved",
+            DeathCause::Hazard => "hazard",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Death {
+    pub turn: u32,
+    pub cause: DeathCause,
+}
+
+#[derive(Debug, Clone)]
+pub struct GameResult {
+    /// Games reaching the maximum number of turns are draws.
+    pub outcome: Outcome,
+    pub turns: u32,
+    /// Indexed by player, `None` for the snakes still alive at the end.
+    pub deaths: Vec<Option<Death>>,
+}
+
+pub struct Arena {
+    width: i32,
+    height: i32,
+    ruleset: &'static dyn Ruleset,
+    settings: Settings,
+    think_time: Duration,
+    max_turns: u32,
+    seed: Option<u64>,
+    players: Vec<(String, PlayerFactory)>,
+}
+
+impl Arena {
+    /// Fails on boards the engine doesn't support, or too small for the snakes to start one
+    /// cell away from the walls.
+    pub fn new(width: i32, height: i32) -> Result<Self, String> {
+        check_size(width, height)?;
+        if width < 3 || height < 3 {
+            return Err(format!("{}x{} board too small for an arena", width, height));
+        }
+
+        Ok(Arena {
+            width,
+            height,
+            ruleset: &Standard,
+            settings: Settings::default(),
+            think_time: Duration::from_millis(100),
+            max_turns: 1000,
+            seed: None,
+            players: vec![],
+        })
+    }
+
+    pub fn with_ruleset(mut self, ruleset: &'static dyn Ruleset) -> Self {
+        self.ruleset = ruleset;
+        self
+    }
+
+    pub fn with_settings(mut self, settings: Settings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Time given to the players to think at every turn, they all think at the same time.
+    pub fn with_think_time(mut self, think_time: Duration) -> Self {
+        self.think_time = think_time;
+        self
+    }
+
+    pub fn with_max_turns(mut self, max_turns: u32) -> Self {
+        self.max_turns = max_turns;
+        self
+    }
+
+    /// Makes the boards, and so the food spawns and start positions, the same from a run to
+    /// another. Players may still use their own randomness.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn with_player(mut self, name: &str, factory: PlayerFactory) -> Self {
+        self.players.push((name.to_string(), factory));
+        self
+    }
+
+    #[inline]
+    pub fn player_names(&self) -> impl Iterator<Item = &str> {
+        self.players.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Plays `nb_games` games, `on_game` is called after each of them.
+    pub fn run(&self, nb_games: usize, mut on_game: impl FnMut(usize, &GameResult)) -> ArenaStats {
+        let mut stats = ArenaStats::new(self.player_names());
+        for i in 0..nb_games {
+            let seed = match self.seed {
+                Some(seed) => seed.wrapping_add(i as u64),
+                None => rand::random(),
+            };
+
+            let result = self.play_game(seed);
+            on_game(i, &result);
+            stats.add(&result);
+        }
+
+        stats
+    }
+
+    pub fn play_game(&self, seed: u64) -> GameResult {
+        let mut game = SnakeGame::new(self.new_board(seed));
+        let mut players: Vec<Box<dyn Player>> = self
+            .players
+            .iter()
+            .enumerate()
+            .map(|(id, (_, factory))| factory(id))
+            .collect();
+        let mut deaths = vec![None; players.len()];
+
+        while !game.board().is_game_over() && game.board().turn() < self.max_turns {
+            players.iter_mut().enumerate().for_each(|(id, p)| {
+                game.set_player(id);
+                p.think(&game);
+            });
+            if !self.think_time.is_zero() {
+                thread::sleep(self.think_time);
+            }
+
+            let moves = players.iter_mut().map(|p| p.next_move()).collect();
+            let alive_before: Vec<bool> =
+                game.board().snakes().iter().map(|s| !s.is_dead()).collect();
+            game.step(moves);
+
+            let board = game.board();
+            board
+                .snakes()
+                .iter()
+                .enumerate()
+                .filter(|(id, s)| alive_before[*id] && s.is_dead())
+                .for_each(|(id, _)| {
+                    deaths[id] = Some(Death {
+                        turn: board.turn(),
+                        cause: death_cause(board, id),
+                    })
+                });
+        }
+
+        let board = game.board();
+        let outcome = match board.outcome() {
+            Outcome::Ongoing => Outcome::Draw,
+            outcome => outcome,
+        };
+
+        GameResult {
+            outcome,
+            turns: board.turn(),
+            deaths,
+        }
+    }
+
+    /// Snakes start one cell away from the walls, on spots shuffled from a game to another so
+    /// that no player keeps the best one.
+    fn new_board(&self, seed: u64) -> Board {
+        let (w, h) = (self.width, self.height);
+        let mut spots = vec![
+            Point { x: 1, y: 1 },
+            Point { x: w - 2, y: h - 2 },
+            Point { x: 1, y: h - 2 },
+            Point { x: w - 2, y: 1 },
+            Point { x: w / 2, y: 1 },
+            Point { x: w / 2, y: h - 2 },
+            Point { x: 1, y: h / 2 },
+            Point { x: w - 2, y: h / 2 },
+        ];
+        assert!(
+            self.players.len() <= spots.len(),
+            "at most {} players",
+            spots.len()
+        );
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        spots.shuffle(&mut rng);
+        let snakes = spots
+            .into_iter()
+            .take(self.players.len())
+            .map(Snake::new)
+            .collect();
+
+        Board::new(w, h, snakes)
+            .with_ruleset(self.ruleset)
+            .with_settings(self.settings)
+            .with_seed(rng.gen())
+    }
+}
+
+/// Cause of death of the snake `id`, killed during the last step of `board`.
+fn death_cause(board: &Board, id: usize) -> DeathCause {
+    let collision = board
+        .collisions()
+        .iter()
+        .find(|c| c.id() == id && c.causes_death());
+
+    match collision {
+        Some(Collision::Wall { .. }) => DeathCause::Wall,
+        Some(Collision::SelfBody { .. }) => DeathCause::SelfBody,
+        Some(Collision::OtherBody { .. }) => DeathCause::OtherBody,
+        Some(Collision::HeadToHead { .. }) => DeathCause::HeadToHead,
+        None if board.is_hazard(*board.snakes()[id].head()) => DeathCause::Hazard,
+        None => DeathCause::Starvation,
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PlayerStats {
+    pub name: String,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    pub deaths: HashMap<DeathCause, usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ArenaStats {
+    pub nb_games: usize,
+    pub total_turns: u64,
+    pub players: Vec<PlayerStats>,
+}
+
+impl ArenaStats {
+    pub fn new<'a>(names: impl Iterator<Item = &'a str>) -> Self {
+        ArenaStats {
+            nb_games: 0,
+            total_turns: 0,
+            players: names
+                .map(|name| PlayerStats {
+                    name: name.to_string(),
+                    ..PlayerStats::default()
+                })
+                .collect(),
+        }
+    }
+
+    pub fn add(&mut self, result: &GameResult) {
+        self.nb_games += 1;
+        self.total_turns += result.turns as u64;
+
+        self.players.iter_mut().enumerate().for_each(|(id, p)| {
+            match result.outcome {
+                Outcome::Win { id: winner } if winner == id => p.wins += 1,
+                Outcome::Draw | Outcome::Ongoing => p.draws += 1,
+                Outcome::Win { .. } => p.losses += 1,
+            }
+
+            if let Some(death) = result.deaths[id] {
+                *p.deaths.entry(death.cause).or_insert(0) += 1;
+            }
+        });
+    }
+
+    pub fn average_length(&self) -> f64 {
+        if self.nb_games == 0 {
+            0.
+        } else {
+            self.total_turns as f64 / self.nb_games as f64
+        }
+    }
+}
+
+impl fmt::Display for ArenaStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} games, {:.1} turns on average",
+            self.nb_games,
+            self.average_length()
+        )?;
+
+        write!(
+            f,
+            "{:<16}{:>6}{:>6}{:>7}",
+            "player", "wins", "draws", "losses"
+        )?;
+        for cause in DeathCause::ALL.iter() {
+            write!(f, "{:>9}", cause.name())?;
+        }
+        writeln!(f)?;
+
+        for p in self.players.iter() {
+            write!(
+                f,
+                "{:<16}{:>6}{:>6}{:>7}",
+                p.name, p.wins, p.draws, p.losses
+            )?;
+            for cause in DeathCause::ALL.iter() {
+                write!(f, "{:>9}", p.deaths.get(cause).unwrap_or(&0))?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::{Movement, RandomBot};
+
+    #[test]
+    fn arena_counts_every_game() {
+        let arena = Arena::new(7, 7)
+            .unwrap()
+            .with_think_time(Duration::from_millis(0))
+            .with_max_turns(50)
+            .with_seed(5)
+            .with_player("a", Box::new(|id| Box::new(RandomBot::new(id, [0.; 4]))))
+            .with_player("b", Box::new(|id| Box::new(RandomBot::new(id, [0.; 4]))));
+
+        let mut nb_calls = 0;
+        let stats = arena.run(4, |_, _| nb_calls += 1);
+
+        assert_eq!(nb_calls, 4);
+        assert_eq!(stats.nb_games, 4);
+        for p in stats.players.iter() {
+            assert_eq!(p.wins + p.draws + p.losses, 4);
+        }
+        assert_eq!(stats.players[0].wins, stats.players[1].losses);
+    }
+
+    #[test]
+    fn unsupported_sizes_are_refused() {
+        assert!(Arena::new(0, 11).is_err());
+        assert!(Arena::new(30, 30).is_err());
+        assert!(Arena::new(2, 11).is_err());
+        assert!(Arena::new(3, 3).is_ok());
+    }
+
+    #[test]
+    fn wall_deaths() {
+        let snakes = vec![
+            Snake::new(Point { x: 0, y: 0 }),
+            Snake::new(Point { x: 4, y: 4 }),
+        ];
+        let mut board = Board::new(5, 5, snakes);
+        board.step(vec![Movement::Left, Movement::Left], false);
+
+        assert_eq!(death_cause(&board, 0), DeathCause::Wall);
+    }
+}
@@ -0,0 +1,310 @@
+//! Sets of cells packed in bits, row after row from the bottom left corner.
+//!
+//! A move of every cell of a set at once is a shift of its bits, so flood fills and the like
+//! go through the whole board a few words at a time instead of cell by cell.
+//!
+//! The matrice keeps a single set of the cells of all the bodies: its array already tells
+//! which snake is on a cell, for the few checks needing it. Bodies are checked against that set
+//! first, the exact check against the parts of the snakes only runs on a hit.
+
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign};
+
+use crate::engine::{Geometry, Point};
+
+const WORDS: usize = 10;
+/// Boards can't have more cells than this, 25x25 boards fit.
+pub(crate) const MAX_CELLS: usize = 64 * WORDS;
+
+/// Tells whether boards of `width` by `height` cells fit in a `BitBoard`, for the boards coming
+/// from outside of the engine.
+pub(crate) fn check_size(width: i32, height: i32) -> Result<(), String> {
+    let cells = width as i64 * height as i64;
+    if width <= 0 || height <= 0 || cells > MAX_CELLS as i64 {
+        return Err(format!(
+            "unsupported {}x{} board, boards have 1 to {} cells",
+            width, height, MAX_CELLS
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Hash, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub(crate) struct BitBoard([u64; WORDS]);
+
+impl BitBoard {
+    #[inline]
+    pub fn contains(&self, i: usize) -> bool {
+        self.0[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    #[inline]
+    pub fn insert(&mut self, i: usize) {
+        self.0[i / 64] |= 1 << (i % 64);
+    }
+
+    #[inline]
+    pub fn remove(&mut self, i: usize) {
+        self.0[i / 64] &= !(1 << (i % 64));
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    #[cfg(feature = "search")]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&w| w == 0)
+    }
+
+    /// Cells of `self` that aren't in `other`.
+    #[inline]
+    pub fn difference(&self, other: &BitBoard) -> BitBoard {
+        let mut bits = *self;
+        bits.0.iter_mut().zip(&other.0).for_each(|(a, b)| *a &= !b);
+        bits
+    }
+
+    /// Moves every cell `n` indices up.
+    fn shl(&self, n: usize) -> BitBoard {
+        let (words, bits) = (n / 64, n % 64);
+        let mut shifted = BitBoard::default();
+        for i in words..WORDS {
+            shifted.0[i] = self.0[i - words] << bits;
+            if bits > 0 && i > words {
+                shifted.0[i] |= self.0[i - words - 1] >> (64 - bits);
+            }
+        }
+        shifted
+    }
+
+    /// Moves every cell `n` indices down.
+    fn shr(&self, n: usize) -> BitBoard {
+        let (words, bits) = (n / 64, n % 64);
+        let mut shifted = BitBoard::default();
+        for i in 0..WORDS - words {
+            shifted.0[i] = self.0[i + words] >> bits;
+            if bits > 0 && i + words + 1 < WORDS {
+                shifted.0[i] |= self.0[i + words + 1] << (64 - bits);
+            }
+        }
+        shifted
+    }
+}
+
+impl BitOr for BitBoard {
+    type Output = BitBoard;
+
+    #[inline]
+    fn bitor(mut self, other: BitBoard) -> BitBoard {
+        self |= other;
+        self
+    }
+}
+
+impl BitOrAssign for BitBoard {
+    #[inline]
+    fn bitor_assign(&mut self, other: BitBoard) {
+        self.0.iter_mut().zip(&other.0).for_each(|(a, b)| *a |= b);
+    }
+}
+
+impl BitAnd for BitBoard {
+    type Output = BitBoard;
+
+    #[inline]
+    fn bitand(mut self, other: BitBoard) -> BitBoard {
+        self &= other;
+        self
+    }
+}
+
+impl BitAndAssign for BitBoard {
+    #[inline]
+    fn bitand_assign(&mut self, other: BitBoard) {
+        self.0.iter_mut().zip(&other.0).for_each(|(a, b)| *a &= b);
+    }
+}
+
+/// Where the cells of a board are in a `BitBoard`, with the masks of its edges.
+#[derive(Hash, Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Layout {
+    geometry: Geometry,
+    cells: BitBoard,
+    left: BitBoard,
+    right: BitBoard,
+    bottom: BitBoard,
+    top: BitBoard,
+}
+
+impl Layout {
+    pub fn new(geometry: Geometry) -> Self {
+        let (width, height) = (geometry.width, geometry.height);
+        assert!(
+            (width * height) as usize <= MAX_CELLS,
+            "boards have at most {} cells",
+            MAX_CELLS
+        );
+
+        let mut layout = Layout {
+            geometry,
+            cells: BitBoard::default(),
+            left: BitBoard::default(),
+            right: BitBoard::default(),
+            bottom: BitBoard::default(),
+            top: BitBoard::default(),
+        };
+        for y in 0..height {
+            for x in 0..width {
+                let i = layout.index(Point { x, y });
+                layout.cells.insert(i);
+                if x == 0 {
+                    layout.left.insert(i);
+                }
+                if x == width - 1 {
+                    layout.right.insert(i);
+                }
+                if y == 0 {
+                    layout.bottom.insert(i);
+                }
+                if y == height - 1 {
+                    layout.top.insert(i);
+                }
+            }
+        }
+
+        layout
+    }
+
+    #[inline]
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    /// Index of the cell of `p`, which must be on the board.
+    #[inline]
+    pub fn index(&self, p: Point) -> usize {
+        debug_assert!(!self.geometry.is_outside(p));
+        (p.y * self.geometry.width + p.x) as usize
+    }
+
+    /// Every cell of the board.
+    #[inline]
+    pub fn cells(&self) -> BitBoard {
+        self.cells
+    }
+
+    /// Cells one move away from a cell of `bits`.
+    pub fn neighbours(&self, bits: &BitBoard) -> BitBoard {
+        let (width, height) = (self.geometry.width as usize, self.geometry.height as usize);
+
+        let mut neighbours = bits.difference(&self.right).shl(1)
+            | bits.difference(&self.left).shr(1)
+            | bits.shl(width)
+            | bits.shr(width);
+        if self.geometry.wrapped {
+            neighbours |= (*bits & self.right).shr(width - 1)
+                | (*bits & self.left).shl(width - 1)
+                | (*bits & self.top).shr(width * (height - 1))
+                | (*bits & self.bottom).shl(width * (height - 1));
+        }
+
+        neighbours & self.cells
+    }
+
+    /// Cells of `free` reachable from `start` through the others, `start` included.
+    pub fn flood_fill(&self, start: BitBoard, free: &BitBoard) -> BitBoard {
+        let mut reached = start;
+        loop {
+            let next = reached | (self.neighbours(&reached) & *free);
+            if next == reached {
+                return reached;
+            }
+            reached = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::Movement;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use std::collections::{HashSet, VecDeque};
+
+    const MOVES: [Movement; 4] = [
+        Movement::Down,
+        Movement::Left,
+        Movement::Up,
+        Movement::Right,
+    ];
+
+    #[test]
+    fn neighbours_match_the_geometry() {
+        // words are crossed by the 13 cells wide rows
+        for &wrapped in &[false, true] {
+            let geometry = Geometry::new(13, 11, wrapped);
+            let layout = Layout::new(geometry);
+            for y in 0..geometry.height {
+                for x in 0..geometry.width {
+                    let p = Point { x, y };
+                    let mut cell = BitBoard::default();
+                    cell.insert(layout.index(p));
+
+                    let mut expected = BitBoard::default();
+                    MOVES
+                        .iter()
+                        .filter_map(|&m| geometry.neighbour(p, m))
+                        .for_each(|n| expected.insert(layout.index(n)));
+
+                    assert_eq!(layout.neighbours(&cell), expected, "{:?} {}", p, wrapped);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn flood_fill_reaches_what_a_search_reaches() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for &wrapped in &[false, true] {
+            let geometry = Geometry::new(19, 17, wrapped);
+            let layout = Layout::new(geometry);
+
+            for _ in 0..20 {
+                let walls: HashSet<Point> = (0..120)
+                    .map(|_| Point {
+                        x: rng.gen_range(0..geometry.width),
+                        y: rng.gen_range(0..geometry.height),
+                    })
+                    .collect();
+                let start = Point {
+                    x: rng.gen_range(0..geometry.width),
+                    y: rng.gen_range(0..geometry.height),
+                };
+
+                // a search a cell at a time, the start being free or not
+                let mut reached: HashSet<Point> = vec![start].into_iter().collect();
+                let mut queue: VecDeque<Point> = vec![start].into_iter().collect();
+                while let Some(p) = queue.pop_front() {
+                    for n in MOVES.iter().filter_map(|&m| geometry.neighbour(p, m)) {
+                        if !walls.contains(&n) && reached.insert(n) {
+                            queue.push_back(n);
+                        }
+                    }
+                }
+
+                let mut free = layout.cells();
+                walls.iter().for_each(|&p| free.remove(layout.index(p)));
+                let mut cells = BitBoard::default();
+                cells.insert(layout.index(start));
+
+                let mut expected = BitBoard::default();
+                reached
+                    .iter()
+                    .for_each(|&p| expected.insert(layout.index(p)));
+                assert_eq!(layout.flood_fill(cells, &free), expected);
+            }
+        }
+    }
+}
@@ -1,28 +1,56 @@
 use itertools::Itertools;
 use ndarray::Array2;
 use rand::prelude::SliceRandom;
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
 
-use crate::engine::matrice::Matrice;
-use std::{collections::HashSet, iter::FromIterator};
+use crate::engine::bitboard::BitBoard;
+use crate::engine::matrice::{CellValue, Matrice};
+use crate::engine::zobrist;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::hash::Hash;
-use std::vec;
+use std::{mem, vec};
 
 use crate::engine::Movement;
 
-use super::{matrice::Displacement, Collision, Point, Snake};
+use super::{ruleset::Standard, BoardState, Collision, Geometry, Point, Ruleset, Settings, Snake};
 
 pub type SnakeId = u8;
 
-#[derive(Debug, Clone)]
+/// Health lost by a snake ending its turn in a hazard, on top of the regular move cost.
+pub const DEFAULT_HAZARD_DAMAGE: i32 = 14;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    Ongoing,
+    Win { id: usize },
+    /// Every snake died, this is also how solo games end.
+    Draw,
+}
+
+/// Serialized as a `BoardState`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(into = "BoardState", try_from = "BoardState")]
 pub struct Board {
     height: i32,
     width: i32,
     food: HashSet<Point>,
+    hazards: HashSet<Point>,
+    /// Cells of `food` and `hazards`, see `Matrice::layout`.
+    food_cells: BitBoard,
+    hazard_cells: BitBoard,
+    /// Food spawned at the end of the last turn.
+    spawned_food: Vec<Point>,
     snakes: Vec<Snake>,
     matrice: Matrice,
     collisions: Vec<Collision>,
-    food_spawn_chance: f32,
-    food_min_amount: usize,
+    settings: Settings,
+    ruleset: &'static dyn Ruleset,
+    turn: u32,
+    /// Zobrist keys of the food, heads, health and turn, the matrice keeps the ones of the bodies.
+    zobrist: u64,
+    seed: u64,
+    rng: StdRng,
 }
 
 impl Hash for Board {
@@ -30,60 +58,218 @@ impl Hash for Board {
     where
         H: std::hash::Hasher,
     {
-        self.matrice.hash(state);
+        self.zobrist().hash(state);
     }
 }
 
 // Game
 impl Board {
     pub fn new(width: i32, height: i32, snakes: Vec<Snake>) -> Self {
-        Self {
-            food_min_amount: snakes.len() - 1,
-            matrice: Matrice::new(&snakes, height as usize, width as usize),
+        let seed = rand::random();
+        let mut board = Self {
+            matrice: Matrice::new(&snakes, Geometry::new(width, height, false)),
             height,
             width,
             snakes,
             collisions: vec![],
             food: HashSet::new(),
-            food_spawn_chance: 0.15,
-        }
+            hazards: HashSet::new(),
+            food_cells: BitBoard::default(),
+            hazard_cells: BitBoard::default(),
+            spawned_food: vec![],
+            settings: Settings::default(),
+            ruleset: &Standard,
+            turn: 0,
+            zobrist: 0,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        };
+        board.zobrist = board.snakes_zobrist();
+        board
     }
 
-    pub fn new_from(width: i32, height: i32, snakes: Vec<Snake>, food: &[Point]) -> Self {
-        Self {
-            food_min_amount: snakes.len() - 1,
-            matrice: Matrice::new(&snakes, height as usize, width as usize),
-            height,
-            width,
-            snakes,
-            collisions: vec![],
-            food: HashSet::from_iter(food.iter().map(|p| *p)),
-            food_spawn_chance: 0.15,
-        }
+    pub fn new_from(
+        width: i32,
+        height: i32,
+        snakes: Vec<Snake>,
+        food: &[Point],
+        hazards: &[Point],
+    ) -> Self {
+        let mut board = Board::new(width, height, snakes);
+        food.iter().for_each(|&p| board.add_food(p));
+        board.add_hazards(hazards.iter().copied());
+        board
     }
 
     pub fn step(&mut self, movs: Vec<Movement>, is_simulation: bool) {
+        self.make_move(&movs, &mut Undo::default());
+        self.spawned_food.clear();
+
+        // spawn food and move hazards, from the generator of the board lent to the ruleset
+        if !is_simulation {
+            let mut rng = self.rng.clone();
+            self.spawn_food(&mut rng);
+            self.rng = rng;
+            self.update_hazards();
+        }
+    }
+
+    /// Spawns food at the end of a turn following the ruleset, drawn from `rng`. The search
+    /// samples spawns from a generator of its own, leaving the game `seed` replays untouched.
+    pub fn spawn_food(&mut self, rng: &mut dyn RngCore) {
+        let food_before = self.food_cells;
+        let ruleset = self.ruleset;
+        ruleset.spawn_food(self, rng);
+
+        // food only spawns on free cells, the cells of the food before are left as they were
+        let layout = self.matrice.layout();
+        self.spawned_food.extend(
+            self.food
+                .iter()
+                .filter(|&&p| !food_before.contains(layout.index(p))),
+        );
+    }
+
+    /// Moves the hazards at the end of a turn following the ruleset.
+    pub fn update_hazards(&mut self) {
+        let ruleset = self.ruleset;
+        ruleset.update_hazards(self);
+    }
+
+    /// Plays a simulated turn, like `step`, writing what changed to `undo` for `unmake_move`.
+    ///
+    /// Reusing the same records from a turn to the next, one per turn played ahead, the turn
+    /// is played without allocating once their buffers are large enough.
+    pub fn make_move(&mut self, movs: &[Movement], undo: &mut Undo) {
         debug_assert_eq!(self.snakes.len(), movs.len());
 
+        let ruleset = self.ruleset;
+        undo.clear();
+        undo.turn = self.turn;
+        undo.zobrist = self.zobrist;
+        self.zobrist ^= self.snakes_zobrist();
+
         // Move all alive snakes
-        let displacements = self.update_snakes_positions(movs);
+        self.update_snakes_positions(movs, undo);
 
         // Kill collided snakes
-        self.kill_collided_snakes();
+        self.kill_collided_snakes(undo);
 
         // Feed snakes
-        self.feed_snakes();
+        ruleset.feed_snakes(self, &mut undo.eaten);
+
+        // Hurt snakes standing in hazards
+        self.damage_snakes_in_hazards(&undo.eaten);
 
         // kill hungry snakes
-        self.kill_hungry_snakes();
+        self.kill_hungry_snakes(undo);
 
-        // spawn food
-        if !is_simulation {
-            self.spawn_food();
-        }
+        self.turn += 1;
 
         // update matrice
-        self.update_matrice(displacements); // FIXME: remove clone
+        self.update_matrice(undo);
+
+        self.zobrist ^= self.snakes_zobrist() ^ zobrist::turn(undo.turn) ^ zobrist::turn(self.turn);
+    }
+
+    /// Takes back the turn played by `make_move`, the last one played.
+    pub fn unmake_move(&mut self, undo: &Undo) {
+        undo.cells
+            .iter()
+            .rev()
+            .for_each(|&(p, v)| self.matrice.set(p, v));
+
+        undo.eaten.iter().for_each(|&p| self.add_food(p));
+
+        let mut end = undo.bodies.len();
+        undo.killed.iter().rev().for_each(|&(id, start)| {
+            self.snakes[id].revive(&undo.bodies[start..end]);
+            end = start;
+        });
+
+        undo.moved
+            .iter()
+            .rev()
+            .for_each(|m| self.snakes[m.id].undo_move(m.head, m.tail, m.health, m.length));
+
+        self.collisions.clear();
+        self.collisions.extend(undo.collisions.iter().cloned());
+        self.turn = undo.turn;
+        self.zobrist = undo.zobrist;
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct MovedSnake {
+    id: usize,
+    head: Point,
+    health: i32,
+    length: usize,
+    /// Removed from the body by the move.
+    tail: Option<Point>,
+}
+
+/// What `Board::make_move` changed, for `Board::unmake_move` to take it back.
+#[derive(Debug, Clone, Default)]
+pub struct Undo {
+    turn: u32,
+    zobrist: u64,
+    /// The snakes alive before the move, as they were.
+    moved: Vec<MovedSnake>,
+    /// Snakes killed during the move and where their body starts in `bodies`.
+    killed: Vec<(usize, usize)>,
+    bodies: Vec<Point>,
+    eaten: Vec<Point>,
+    /// Collisions of the turn before.
+    collisions: Vec<Collision>,
+    /// Cells of the matrice in the order they changed, with their previous value.
+    cells: Vec<(Point, CellValue)>,
+}
+
+impl Undo {
+    fn clear(&mut self) {
+        self.moved.clear();
+        self.killed.clear();
+        self.bodies.clear();
+        self.eaten.clear();
+        self.collisions.clear();
+        self.cells.clear();
+    }
+}
+
+// Settings
+impl Board {
+    pub fn with_ruleset(mut self, ruleset: &'static dyn Ruleset) -> Self {
+        self.ruleset = ruleset;
+        self.matrice.set_geometry(Geometry::new(
+            self.width,
+            self.height,
+            ruleset.wraps_edges(),
+        ));
+        self
+    }
+
+    pub fn with_settings(mut self, settings: Settings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn with_turn(mut self, turn: u32) -> Self {
+        self.zobrist ^= zobrist::turn(self.turn) ^ zobrist::turn(turn);
+        self.turn = turn;
+        self
+    }
+
+    /// Restarts the random number generator, two boards built with the same seed and
+    /// stepped with the same movements play exactly the same game.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.reseed(seed);
+        self
+    }
+
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
     }
 }
 
@@ -108,16 +294,6 @@ impl Board {
             .filter(|(_, s)| !s.is_dead())
     }
 
-    pub fn update_matrice(&mut self, displacements: Vec<Displacement>) {
-        self.matrice.update(
-            displacements
-                .into_iter()
-                .filter(|(id, ..)| !self.snakes[*id as usize].is_dead())
-                .collect(),
-            &self.collisions,
-        );
-    }
-
     #[inline]
     pub fn nb_snakes_alive(&self) -> usize {
         self.alive_snakes().count()
@@ -143,85 +319,295 @@ impl Board {
         &self.food
     }
 
+    #[inline]
+    pub fn hazards(&self) -> &HashSet<Point> {
+        &self.hazards
+    }
+
+    /// Food spawned at the end of the turn played by the last `step`, even on a cell where
+    /// food got eaten during the turn.
+    #[inline]
+    pub fn spawned_food(&self) -> &[Point] {
+        &self.spawned_food
+    }
+
+    #[cfg(feature = "search")]
+    #[inline]
+    pub(crate) fn hazard_cells(&self) -> &BitBoard {
+        &self.hazard_cells
+    }
+
+    #[inline]
+    pub fn is_food(&self, p: Point) -> bool {
+        !self.is_outside(p) && self.food_cells.contains(self.matrice.layout().index(p))
+    }
+
+    #[inline]
+    pub fn is_hazard(&self, p: Point) -> bool {
+        !self.is_outside(p) && self.hazard_cells.contains(self.matrice.layout().index(p))
+    }
+
+    #[inline]
+    pub fn hazard_damage(&self) -> i32 {
+        self.settings.hazard_damage_per_turn
+    }
+
+    #[inline]
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    #[inline]
+    pub fn ruleset(&self) -> &'static dyn Ruleset {
+        self.ruleset
+    }
+
+    #[inline]
+    pub fn turn(&self) -> u32 {
+        self.turn
+    }
+
+    /// Zobrist hash of the position: the bodies, heads, lengths, health by tens and food of the
+    /// snakes, and the parity of the turn. Kept up to date by the moves, it costs nothing to read.
+    #[inline]
+    pub fn zobrist(&self) -> u64 {
+        self.zobrist ^ self.matrice.zobrist()
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        if !self.ruleset.is_game_over(self) {
+            return Outcome::Ongoing;
+        }
+
+        match self.alive_snakes().next() {
+            Some((id, _)) => Outcome::Win { id },
+            None => Outcome::Draw,
+        }
+    }
+
+    #[inline]
+    pub fn is_game_over(&self) -> bool {
+        self.outcome() != Outcome::Ongoing
+    }
+
+    #[inline]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    #[inline]
+    pub(crate) fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+
+    #[inline]
+    pub fn geometry(&self) -> Geometry {
+        self.matrice.geometry()
+    }
+
     #[inline]
     pub fn is_outside(&self, p: Point) -> bool {
-        p.x < 0 || p.x >= self.width || p.y < 0 || p.y >= self.height
+        self.geometry().is_outside(p)
     }
 }
 
 // engine logic
 impl Board {
-    fn update_snakes_positions(&mut self, movs: Vec<Movement>) -> Vec<Displacement> {
-        self.alive_snakes_mut()
-            .map(|(i, s)| (i as SnakeId, s.apply_move(movs[i])))
-            .collect()
+    fn update_snakes_positions(&mut self, movs: &[Movement], undo: &mut Undo) {
+        let geometry = self.geometry();
+
+        self.alive_snakes_mut().for_each(|(id, s)| {
+            let (head, health, length) = (*s.head(), s.health(), s.length());
+            let (tail, _) = s.move_head_to(geometry.apply_mov(head, movs[id]));
+            undo.moved.push(MovedSnake {
+                id,
+                head,
+                health,
+                length,
+                tail,
+            });
+        });
     }
 
-    fn kill_hungry_snakes(&mut self) {
-        let hungry_snakes: Vec<usize> = self
-            .alive_snakes()
-            .filter(|(_, s)| s.health() == 0)
-            .map(|(i, _)| i)
-            .collect();
+    fn kill_hungry_snakes(&mut self, undo: &mut Undo) {
+        for id in 0..self.snakes.len() {
+            if !self.snakes[id].is_dead() && self.snakes[id].health() == 0 {
+                self.kill_snake(id, undo);
+            }
+        }
+    }
 
-        self.kill_snakes(hungry_snakes);
+    pub(crate) fn feed_snakes(&mut self, eaten: &mut Vec<Point>) {
+        let (food, food_cells, hash) = (&mut self.food, &mut self.food_cells, &mut self.zobrist);
+        let layout = self.matrice.layout();
+        self.snakes
+            .iter_mut()
+            .filter(|s| !s.is_dead())
+            .for_each(|s| {
+                // snakes meeting on a food all eat it
+                if food.remove(s.head()) {
+                    let i = layout.index(*s.head());
+                    food_cells.remove(i);
+                    *hash ^= zobrist::food(i);
+                    eaten.push(*s.head());
+                }
+                if eaten.contains(s.head()) {
+                    s.feed();
+                }
+            });
     }
 
-    fn feed_snakes(&mut self) {
-        let snake_heads: HashSet<Point> = self.alive_snakes().map(|(_, s)| *s.head()).collect();
+    fn add_food(&mut self, p: Point) {
+        if self.food.insert(p) {
+            let i = self.matrice.layout().index(p);
+            self.food_cells.insert(i);
+            self.zobrist ^= zobrist::food(i);
+        }
+    }
 
-        let food_available: HashSet<Point> = self
-            .food
-            .drain_filter(|f| snake_heads.contains(f))
-            .collect();
+    /// Zobrist keys of the heads, health and lengths of the snakes alive.
+    fn snakes_zobrist(&self) -> u64 {
+        let layout = self.matrice.layout();
+        self.alive_snakes().fold(0, |hash, (id, s)| {
+            hash ^ zobrist::head(id, layout.index(*s.head()))
+                ^ zobrist::health(id, s.health())
+                ^ zobrist::length(id, s.length())
+        })
+    }
 
-        self.alive_snakes_mut()
-            .filter(|(_, s)| food_available.contains(s.head()))
-            .for_each(|(_, s)| s.feed());
+    fn add_hazards(&mut self, hazards: impl Iterator<Item = Point>) {
+        for p in hazards {
+            self.hazards.insert(p);
+            self.hazard_cells.insert(self.matrice.layout().index(p));
+        }
     }
 
-    fn kill_collided_snakes(&mut self) {
-        // Compute all collisions
-        self.collisions = self
-            .alive_snakes()
-            .filter_map(|(i, _)| self.check_collision(i))
-            .collect();
+    fn damage_snakes_in_hazards(&mut self, eaten_food: &[Point]) {
+        if self.hazards.is_empty() {
+            return;
+        }
 
-        let snakes_to_kill: Vec<usize> = self
-            .collisions
-            .iter()
-            .filter(|&c| c.causes_death())
-            .map(|c| match *c {
-                Collision::Wall { id } => id,
-                Collision::SelfBody { id } => id,
-                Collision::OtherBody { id_1, .. } => id_1,
-                Collision::HeadToHead { id_1, .. } => id_1,
-            })
-            .collect();
+        // snakes eating inside a hazard are spared, like on the official server
+        let (hazards, damage) = (&self.hazards, self.settings.hazard_damage_per_turn);
+        self.snakes
+            .iter_mut()
+            .filter(|s| !s.is_dead())
+            .filter(|s| hazards.contains(s.head()) && !eaten_food.contains(s.head()))
+            .for_each(|s| s.damage(damage));
+    }
+
+    fn kill_collided_snakes(&mut self, undo: &mut Undo) {
+        undo.collisions.append(&mut self.collisions);
+
+        // Compute all collisions
+        let mut collisions = mem::take(&mut self.collisions);
+        collisions.extend(
+            (0..self.snakes.len())
+                .filter(|&i| !self.snakes[i].is_dead())
+                .filter_map(|i| self.check_collision(i)),
+        );
+        self.collisions = collisions;
 
         // kill snakes that got killing collision
-        self.kill_snakes(snakes_to_kill);
+        for i in 0..self.collisions.len() {
+            if self.collisions[i].causes_death() {
+                self.kill_snake(self.collisions[i].id(), undo);
+            }
+        }
+    }
+
+    fn update_matrice(&mut self, undo: &mut Undo) {
+        // fix collisions overrides
+        for i in 0..self.collisions.len() {
+            let owner = match self.collisions[i] {
+                Collision::OtherBody { id_2, loc, .. } => Some((id_2, loc)),
+                Collision::HeadToHead {
+                    src_length,
+                    dst_length,
+                    id_1,
+                    id_2,
+                    loc,
+                } => match src_length {
+                    _ if src_length < dst_length => Some((id_2, loc)),
+                    _ if src_length > dst_length => Some((id_1, loc)),
+                    _ => None,
+                },
+                _ => None,
+            };
+            if let Some((id, p)) = owner {
+                self.set_cell(p, id as CellValue + 1, &mut undo.cells);
+            }
+        }
+
+        // apply displacements, the tails leaving before the heads come in as heads may follow
+        // tails, and stacked tails leaving a part of the body behind
+        for i in 0..undo.moved.len() {
+            let MovedSnake { id, tail, .. } = undo.moved[i];
+            let snake = &self.snakes[id];
+            match tail {
+                Some(tail) if !snake.is_dead() && snake.body().front() != Some(&tail) => {
+                    self.set_cell(tail, 0, &mut undo.cells)
+                }
+                _ => {}
+            }
+        }
+        for i in 0..undo.moved.len() {
+            let id = undo.moved[i].id;
+            if !self.snakes[id].is_dead() {
+                let head = *self.snakes[id].head();
+                self.set_cell(head, id as CellValue + 1, &mut undo.cells);
+            }
+        }
     }
 
-    fn spawn_food(&mut self) {
+    #[inline]
+    fn set_cell(&mut self, p: Point, v: CellValue, cells: &mut Vec<(Point, CellValue)>) {
+        cells.push((p, self.matrice.cell(p)));
+        self.matrice.set(p, v);
+    }
+
+    /// Tops the food up to the minimum, or spawns one with the chance of the settings.
+    pub(crate) fn spawn_food_by_chance(&mut self, rng: &mut dyn RngCore) {
         let nb_curr_food = self.food.len();
-        if self.food.len() < self.food_min_amount {
-            self.spawn_food_rnd(self.food_min_amount - nb_curr_food);
-        } else if rand::random::<f32>() < self.food_spawn_chance {
-            self.spawn_food_rnd(1);
+        if self.food.len() < self.settings.minimum_food {
+            self.spawn_food_rnd(self.settings.minimum_food - nb_curr_food, rng);
+        } else if rng.gen::<f32>() < self.settings.food_spawn_chance {
+            self.spawn_food_rnd(1, rng);
         }
     }
 
-    fn spawn_food_rnd(&mut self, n: usize) {
+    fn spawn_food_rnd(&mut self, n: usize, rng: &mut dyn RngCore) {
         let a: Vec<Point> = self
             .unoccupied_points()
-            .choose_multiple(&mut rand::thread_rng(), n)
+            .choose_multiple(rng, n)
             .copied()
             .collect();
 
-        a.iter().for_each(|p| {
-            self.food.insert(*p);
-        });
+        a.iter().for_each(|&p| self.add_food(p));
+    }
+
+    /// Turns the outermost safe row or column on the `side` of the board into hazards.
+    pub(crate) fn shrink_safe_zone(&mut self, side: Movement) {
+        let safe_points: Vec<Point> = (0..self.height)
+            .cartesian_product(0..self.width)
+            .map(|(y, x)| Point { y, x })
+            .filter(|p| !self.hazards.contains(p))
+            .collect();
+
+        let border = match side {
+            Movement::Left => safe_points.iter().map(|p| p.x).min(),
+            Movement::Right => safe_points.iter().map(|p| p.x).max(),
+            Movement::Down => safe_points.iter().map(|p| p.y).min(),
+            Movement::Up => safe_points.iter().map(|p| p.y).max(),
+        };
+
+        if let Some(border) = border {
+            let on_border = |p: &&Point| match side {
+                Movement::Left | Movement::Right => p.x == border,
+                Movement::Down | Movement::Up => p.y == border,
+            };
+            self.add_hazards(safe_points.iter().filter(on_border).copied());
+        }
     }
 
     fn unoccupied_points(&self) -> Vec<Point> {
@@ -243,20 +629,40 @@ impl Board {
 
 // collisions
 impl Board {
-    pub fn kill_snakes(&mut self, snakes_dead: Vec<usize>) {
-        snakes_dead.into_iter().for_each(|i| {
-            let s = &mut self.snakes[i];
-            self.matrice.remove_points(s.body().iter());
-            s.kill();
-        });
+    fn kill_snake(&mut self, id: usize, undo: &mut Undo) {
+        let start = undo.bodies.len();
+        undo.bodies.extend(self.snakes[id].body().iter().copied());
+        undo.killed.push((id, start));
+
+        for i in start..undo.bodies.len() {
+            let p = undo.bodies[i];
+            if !self.is_outside(p) {
+                self.set_cell(p, 0, &mut undo.cells);
+            }
+        }
+        self.snakes[id].kill();
     }
 
     pub fn check_collision(&self, snake_id: usize) -> Option<Collision> {
         debug_assert!(!self.snakes[snake_id].is_dead());
 
+        // bodies only lie on cells of the matrice, the one before the move will do
+        let on_body = || {
+            let head = *self.snakes[snake_id].head();
+            self.matrice
+                .occupied()
+                .contains(self.matrice.layout().index(head))
+        };
+
         self.collides_wall(snake_id)
-            .or_else(|| self.collides_self_body(snake_id))
-            .or_else(|| self.collides_other_body(snake_id))
+            .or_else(|| {
+                if on_body() {
+                    self.collides_self_body(snake_id)
+                        .or_else(|| self.collides_other_body(snake_id))
+                } else {
+                    None
+                }
+            })
             .or_else(|| self.collides_head_to_head(snake_id))
     }
 
@@ -305,3 +711,132 @@ impl Board {
             .next()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hazard_damage_is_skipped_when_eating() {
+        let snakes = vec![
+            Snake::new_from(50, vec![Point { x: 1, y: 1 }], 3, Point { x: 1, y: 1 }),
+            Snake::new_from(50, vec![Point { x: 5, y: 5 }], 3, Point { x: 5, y: 5 }),
+        ];
+        let hazards = [Point { x: 1, y: 2 }, Point { x: 5, y: 6 }];
+        let mut board = Board::new_from(7, 7, snakes, &[Point { x: 5, y: 6 }], &hazards);
+
+        board.step(vec![Movement::Up, Movement::Up], true);
+
+        assert_eq!(board.snakes()[0].health(), 50 - 1 - DEFAULT_HAZARD_DAMAGE);
+        assert_eq!(board.snakes()[1].health(), 100);
+    }
+
+    #[test]
+    fn same_seed_spawns_same_food() {
+        let play = || {
+            let snakes = vec![Snake::new(Point { x: 1, y: 1 }), Snake::new(Point { x: 8, y: 8 })];
+            let mut board = Board::new(11, 11, snakes).with_seed(7);
+            (0..20).for_each(|_| board.step(vec![Movement::Up, Movement::Down], false));
+            let mut food: Vec<(i32, i32)> = board.food().iter().map(|p| (p.x, p.y)).collect();
+            food.sort_unstable();
+            food
+        };
+
+        assert_eq!(play(), play());
+    }
+
+    #[test]
+    fn unmake_move_restores_the_board() {
+        let snakes = vec![
+            Snake::new_from(
+                2,
+                vec![Point { x: 0, y: 1 }, Point { x: 1, y: 1 }],
+                2,
+                Point { x: 1, y: 1 },
+            ),
+            Snake::new(Point { x: 3, y: 3 }),
+            Snake::new(Point { x: 3, y: 1 }),
+        ];
+        let food = [Point { x: 2, y: 3 }, Point { x: 1, y: 2 }];
+        let board = Board::new_from(5, 5, snakes, &food, &[Point { x: 4, y: 1 }]);
+        let state = |b: &Board| {
+            let mut food: Vec<(i32, i32)> = b.food().iter().map(|p| (p.x, p.y)).collect();
+            food.sort_unstable();
+            (
+                b.snakes().clone(),
+                food,
+                b.matrice().clone(),
+                b.collisions().clone(),
+                b.turn(),
+                b.zobrist(),
+            )
+        };
+
+        // eating, a hazard, a head to head won by the longer snake and a snake turning back
+        let turns = [
+            [Movement::Up, Movement::Left, Movement::Right],
+            [Movement::Right, Movement::Down, Movement::Down],
+            [Movement::Right, Movement::Down, Movement::Up],
+        ];
+        let mut undos = vec![Undo::default(); turns.len()];
+        let mut played = board.clone();
+        let mut stepped = board.clone();
+        let mut states = vec![];
+        for (movs, undo) in turns.iter().zip(&mut undos) {
+            states.push(state(&played));
+            played.make_move(movs, undo);
+            stepped.step(movs.to_vec(), true);
+            assert_eq!(state(&played), state(&stepped));
+        }
+        assert_eq!(played.nb_snakes_alive(), 1);
+
+        for undo in undos.iter().rev() {
+            played.unmake_move(undo);
+            assert_eq!(state(&played), states.pop().unwrap());
+        }
+    }
+
+    #[test]
+    fn last_snake_standing_wins() {
+        let snakes = vec![Snake::new(Point { x: 0, y: 0 }), Snake::new(Point { x: 3, y: 3 })];
+        let mut board = Board::new_from(5, 5, snakes, &[], &[]);
+        assert_eq!(board.outcome(), Outcome::Ongoing);
+
+        board.step(vec![Movement::Left, Movement::Up], true);
+
+        assert_eq!(board.turn(), 1);
+        assert_eq!(board.outcome(), Outcome::Win { id: 1 });
+    }
+
+    #[test]
+    fn solo_games_last_until_death() {
+        let snakes = vec![Snake::new(Point { x: 0, y: 0 })];
+        let mut board = Board::new_from(5, 5, snakes, &[], &[])
+            .with_ruleset(crate::engine::ruleset_from_name("solo"));
+
+        board.step(vec![Movement::Up], true);
+        assert_eq!(board.outcome(), Outcome::Ongoing);
+
+        board.step(vec![Movement::Left], true);
+        assert_eq!(board.outcome(), Outcome::Draw);
+    }
+
+    #[test]
+    fn wrapped_snakes_go_through_edges() {
+        let snakes = vec![Snake::new_from(
+            50,
+            vec![Point { x: 1, y: 0 }, Point { x: 0, y: 0 }],
+            2,
+            Point { x: 0, y: 0 },
+        )];
+        let mut board = Board::new_from(5, 5, snakes, &[], &[])
+            .with_ruleset(crate::engine::ruleset_from_name("wrapped"));
+
+        board.step(vec![Movement::Left], true);
+        board.step(vec![Movement::Down], true);
+
+        assert_eq!(board.nb_snakes_alive(), 1);
+        assert_eq!(*board.snakes()[0].head(), Point { x: 4, y: 4 });
+        assert_eq!(board.matrice().get(Point { x: 4, y: 4 }), Some(0));
+    }
+}
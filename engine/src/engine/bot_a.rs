@@ -1,105 +1,262 @@
 use crate::engine::Movement;
 use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use itertools::Itertools;
+use mcts::transposition_table::ApproxTable;
 use mcts::AsyncSearchOwned;
 
-use mcts::{tree_policy::UCTPolicy, MCTSManager};
+use mcts::{MCTSManager, ThreadData};
 
 use super::Player;
-use crate::engine::{MyEvaluator, MyMCTS, SnakeGame};
+use crate::engine::{safe_move, MyEvaluator, MyMCTS, PriorUCTPolicy, SearchMemory, SnakeGame};
 
 pub struct BotA {
     async_search: Option<AsyncSearchOwned<MyMCTS>>,
+    /// Answered when the search gives no move.
+    fallback: Option<Movement>,
+    /// Statistics of the last search, the next one starts from them.
+    memory: Option<SearchMemory>,
     color: [f32; 4],
     n_threads: usize,
+    exploration: f64,
+    pruning_distance: Option<i32>,
     id: usize,
 }
 
+/// Exploration constant of the UCT policy.
+pub const DEFAULT_EXPLORATION: f64 = 1.5;
+/// Positions the transposition table of a search can hold, more than a turn of search makes.
+const TRANSPOSITION_TABLE_SIZE: usize = 1 << 18;
+/// Time between two looks at the best move while searching until a deadline.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
 impl BotA {
     pub fn new(id: usize, n_threads: usize, color: [f32; 4]) -> Self {
         BotA {
             id,
             color,
             n_threads,
+            exploration: DEFAULT_EXPLORATION,
+            pruning_distance: None,
             async_search: None,
+            fallback: None,
+            memory: None,
         }
     }
+
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// Opponents further than `distance` get a single predicted move in the search, see
+    /// `SnakeGame::set_pruning_distance`.
+    pub fn with_pruning_distance(mut self, distance: i32) -> Self {
+        self.pruning_distance = Some(distance);
+        self
+    }
+
+    /// Starts the next search from the statistics of a previous one, see `into_memory`.
+    pub fn with_memory(mut self, memory: SearchMemory) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
+    /// Statistics of the last search, to start the search of the next turn from them when the
+    /// bot doesn't live from a turn to the next.
+    pub fn into_memory(self) -> Option<SearchMemory> {
+        self.memory
+    }
+
+    /// Search of `game`, starting from the statistics of the last search.
+    fn manager(&mut self, game: &SnakeGame) -> MCTSManager<MyMCTS> {
+        let priors = self
+            .memory
+            .take()
+            .map(|memory| memory.priors(game))
+            .unwrap_or_default();
+        let mut searched_game = game.clone();
+        if let Some(distance) = self.pruning_distance {
+            searched_game.set_pruning_distance(Some(distance));
+        }
+        MCTSManager::new(
+            searched_game,
+            MyMCTS,
+            MyEvaluator::new(priors),
+            PriorUCTPolicy::new(self.exploration),
+            ApproxTable::new(TRANSPOSITION_TABLE_SIZE),
+        )
+    }
 }
 
 impl Player for BotA {
     fn think(&mut self, game: &SnakeGame) {
-        let mcts = MCTSManager::new(
-            game.clone(),
-            MyMCTS,
-            MyEvaluator,
-            UCTPolicy::new(1.5),
-            (),
-            // ApproxTable::new(1024),
-        );
-
+        let mcts = self.manager(game);
+        self.fallback = Some(safe_move(game, self.id));
         self.async_search = Some(mcts.into_playout_parallel_async(self.n_threads));
     }
 
     fn next_move(&mut self) -> crate::engine::Movement {
-        let search = std::mem::replace(&mut self.async_search, None);
-        let best_move = match search {
-            Some(search) => {
-                let mcts = search.halt();
-
-                let best_moves = mcts
-                    .tree()
-                    .root_node()
-                    .moves()
-                    .filter(|m| m.visits() > 0)
-                    .map(|m| {
-                        (
-                            m.get_move()[self.id],
-                            m.sum_rewards() as f64 / m.visits() as f64,
-                        )
-                    })
-                    .sorted_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap())
-                    .take(50);
-
-                let moves_eval = best_moves.fold(HashMap::new(), |mut acc, (m, score)| {
-                    let acc_score = acc.entry(m).or_insert_with(Vec::new);
-                    acc_score.push(score);
-                    acc
-                });
-
-                dbg!(mcts.tree().root_state().board().matrice().array());
-                dbg!(&moves_eval);
-
-                let b: HashMap<Movement, f64> = moves_eval
-                    .into_iter()
-                    .map(|(m, v)| (m, v.iter().sum::<f64>() / v.len() as f64))
-                    // .map(|(m, v)|
-                    // (m, v.into_iter().min_by(|a,b| a.partial_cmp(b).unwrap()).unwrap()))
-                    .collect();
-
-                dbg!(&b);
-
-                b.into_iter()
-                    // .map(|(m, v)| (m, v.iter().sum::<f64>() / v.len() as f64))
-                    .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
-                    .map(|(m, _)| m)
-            }
-            None => None,
+        let fallback = self.fallback.take().unwrap_or(Movement::Up);
+        let search = match self.async_search.take() {
+            Some(search) => search,
+            None => return fallback,
         };
 
-        let best_move = best_move.unwrap_or_else(|| {
-            dbg!("WTFFF, answering random movement");
-            rand::random()
-        });
-
-        dbg!(&best_move);
+        // a panic in a search thread is raised again when halting it
+        let id = self.id;
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mcts = search.halt();
+            (best_move(&mcts, id), SearchMemory::new(mcts.tree()))
+        }));
 
-        best_move
+        match result {
+            Ok((best_move, memory)) => {
+                self.memory = Some(memory);
+                best_move.unwrap_or(fallback)
+            }
+            Err(_) => fallback,
+        }
     }
 
     fn get_color(&self) -> [f32; 4] {
         self.color
     }
 
-    fn register_key_event(&mut self, _: piston_window::Button) {}
+    /// Searches the best move of the snake until `deadline`, or until the best move stayed
+    /// the same for half of the time given. Forced moves and passed deadlines are answered
+    /// right away.
+    fn search_until(&mut self, game: &SnakeGame, deadline: Instant) -> Movement {
+        let fallback = safe_move(game, self.id);
+        if game.non_fatal_moves(self.id).len() <= 1 || Instant::now() >= deadline {
+            self.memory = None;
+            return fallback;
+        }
+
+        let mcts = self.manager(game);
+        let (id, stop) = (self.id, AtomicBool::new(false));
+        // a panic in a search thread is raised again when the scope ends
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            thread::scope(|s| {
+                for _ in 0..self.n_threads {
+                    s.spawn(|| {
+                        let mut tld = ThreadData::default();
+                        while !stop.load(Ordering::Relaxed) {
+                            // the tree is full
+                            if !mcts.tree().playout(&mut tld) {
+                                break;
+                            }
+                        }
+                    });
+                }
+
+                let stable = deadline.saturating_duration_since(Instant::now()) / 2;
+                let (mut best, mut since) = (None, Instant::now());
+                loop {
+                    let now = Instant::now();
+                    if now >= deadline || (best.is_some() && now - since >= stable) {
+                        break;
+                    }
+                    thread::sleep(POLL_INTERVAL.min(deadline - now));
+
+                    let current = best_move(&mcts, id);
+                    if current != best {
+                        best = current;
+                        since = Instant::now();
+                    }
+                }
+                stop.store(true, Ordering::Relaxed);
+            });
+            (best_move(&mcts, id), SearchMemory::new(mcts.tree()))
+        }));
+
+        match result {
+            Ok((best_move, memory)) => {
+                self.memory = Some(memory);
+                best_move.unwrap_or(fallback)
+            }
+            Err(_) => fallback,
+        }
+    }
+}
+
+/// Move of snake `id` with the best average reward among the most rewarding joint moves,
+/// priors included.
+fn best_move(mcts: &MCTSManager<MyMCTS>, id: usize) -> Option<Movement> {
+    let best_moves = mcts
+        .tree()
+        .root_node()
+        .moves()
+        .map(|m| (m, m.move_evaluation()))
+        .filter(|(m, prior)| m.visits() + prior.visits > 0)
+        .map(|(m, prior)| {
+            (
+                m.get_move()[id],
+                (m.sum_rewards() + prior.sum_rewards) as f64 / (m.visits() + prior.visits) as f64,
+            )
+        })
+        .sorted_by(|(_, a), (_, b)| b.total_cmp(a))
+        .take(50);
+
+    let moves_eval = best_moves.fold(HashMap::new(), |mut acc, (m, score)| {
+        let acc_score = acc.entry(m).or_insert_with(Vec::new);
+        acc_score.push(score);
+        acc
+    });
+
+    let b: HashMap<Movement, f64> = moves_eval
+        .into_iter()
+        .map(|(m, v)| (m, v.iter().sum::<f64>() / v.len() as f64))
+        // .map(|(m, v)|
+        // (m, v.into_iter().min_by(|a,b| a.partial_cmp(b).unwrap()).unwrap()))
+        .collect();
+
+    b.into_iter()
+        // .map(|(m, v)| (m, v.iter().sum::<f64>() / v.len() as f64))
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(m, _)| m)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::fixtures::{dead_end, snake};
+    use crate::engine::Board;
+
+    #[test]
+    fn search_stops_once_the_best_move_is_clear() {
+        let game = dead_end();
+        let mut bot = BotA::new(0, 2, [0.; 4]);
+        let deadline = Instant::now() + Duration::from_secs(4);
+        assert_eq!(bot.search_until(&game, deadline), Movement::Right);
+        assert!(Instant::now() < deadline);
+        assert!(bot.into_memory().is_some());
+    }
+
+    #[test]
+    fn search_answers_by_the_deadline() {
+        // a passed deadline leaves no time to search
+        let mut bot = BotA::new(0, 2, [0.; 4]);
+        assert_eq!(
+            bot.search_until(&dead_end(), Instant::now()),
+            Movement::Right
+        );
+        assert!(bot.into_memory().is_none());
+
+        // an open board gets searched, still no later than the deadline
+        let snakes = vec![
+            snake(&[(2, 2), (2, 3), (3, 3)]),
+            snake(&[(8, 8), (8, 7), (7, 7)]),
+        ];
+        let game = SnakeGame::new(Board::new_from(11, 11, snakes, &[], &[]));
+        let mut bot = BotA::new(0, 2, [0.; 4]);
+        let deadline = Instant::now() + Duration::from_millis(200);
+        bot.search_until(&game, deadline);
+        assert!(Instant::now() < deadline + POLL_INTERVAL * 4);
+        assert!(bot.into_memory().is_some());
+    }
 }
@@ -0,0 +1,178 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::{bot_a, duct, BotA, DuctBot, ParanoidBot, PlayerFactory, RandomBot};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BotKind {
+    BotA,
+    Duct,
+    Paranoid,
+    Random,
+}
+
+impl BotKind {
+    fn default_exploration(self) -> f64 {
+        match self {
+            BotKind::Duct => duct::DEFAULT_EXPLORATION,
+            _ => bot_a::DEFAULT_EXPLORATION,
+        }
+    }
+}
+
+/// A bot and its parameters, written `kind[:key=value,...]` like `bot_a:threads=4,exploration=1.2`
+/// or `duct:exploration=0.5`, `paranoid` taking none.
+///
+/// The written form is canonical, parameters left to their default are omitted, so that it can
+/// identify the bot in results.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BotConfig {
+    pub kind: BotKind,
+    pub threads: usize,
+    pub exploration: f64,
+    /// See `BotA::with_pruning_distance`.
+    pub pruning_distance: Option<i32>,
+}
+
+const DEFAULT_THREADS: usize = 2;
+
+impl BotConfig {
+    pub fn new(kind: BotKind) -> Self {
+        BotConfig {
+            kind,
+            threads: DEFAULT_THREADS,
+            exploration: kind.default_exploration(),
+            pruning_distance: None,
+        }
+    }
+
+    pub fn factory(&self) -> PlayerFactory {
+        let config = self.clone();
+        match config.kind {
+            BotKind::BotA => Box::new(move |id| {
+                let bot =
+                    BotA::new(id, config.threads, [0.; 4]).with_exploration(config.exploration);
+                Box::new(match config.pruning_distance {
+                    Some(distance) => bot.with_pruning_distance(distance),
+                    None => bot,
+                })
+            }),
+            BotKind::Duct => Box::new(move |id| {
+                Box::new(DuctBot::new(id, [0.; 4]).with_exploration(config.exploration))
+            }),
+            BotKind::Paranoid => Box::new(|id| Box::new(ParanoidBot::new(id, [0.; 4]))),
+            BotKind::Random => Box::new(|id| Box::new(RandomBot::new(id, [0.; 4]))),
+        }
+    }
+}
+
+impl FromStr for BotConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, params) = match s.find(':') {
+            Some(i) => (&s[..i], &s[i + 1..]),
+            None => (s, ""),
+        };
+
+        let mut config = BotConfig::new(match kind {
+            "bot_a" => BotKind::BotA,
+            "duct" => BotKind::Duct,
+            "paranoid" => BotKind::Paranoid,
+            "random" => BotKind::Random,
+            _ => return Err(format!("unknown bot {}", kind)),
+        });
+
+        for param in params.split(',').filter(|p| !p.is_empty()) {
+            let (key, value) = match param.find('=') {
+                Some(i) => (&param[..i], &param[i + 1..]),
+                None => return Err(format!("missing value for {}", param)),
+            };
+            let invalid = || format!("invalid value for {}: {}", key, value);
+
+            match (config.kind, key) {
+                (BotKind::BotA, "threads") => {
+                    config.threads = value.parse().map_err(|_| invalid())?
+                }
+                (BotKind::BotA, "exploration") | (BotKind::Duct, "exploration") => {
+                    config.exploration = value.parse().map_err(|_| invalid())?
+                }
+                (BotKind::BotA, "pruning") => {
+                    config.pruning_distance = Some(value.parse().map_err(|_| invalid())?)
+                }
+                _ => return Err(format!("unknown parameter {} for {}", key, kind)),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+impl fmt::Display for BotConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            BotKind::BotA => {
+                write!(f, "bot_a")?;
+                let mut params = vec![];
+                if self.threads != DEFAULT_THREADS {
+                    params.push(format!("threads={}", self.threads));
+                }
+                if self.exploration != self.kind.default_exploration() {
+                    params.push(format!("exploration={}", self.exploration));
+                }
+                if let Some(distance) = self.pruning_distance {
+                    params.push(format!("pruning={}", distance));
+                }
+                if !params.is_empty() {
+                    write!(f, ":{}", params.join(","))?;
+                }
+                Ok(())
+            }
+            BotKind::Duct => {
+                write!(f, "duct")?;
+                if self.exploration != self.kind.default_exploration() {
+                    write!(f, ":exploration={}", self.exploration)?;
+                }
+                Ok(())
+            }
+            BotKind::Paranoid => write!(f, "paranoid"),
+            BotKind::Random => write!(f, "random"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bot_config_round_trip() {
+        let config: BotConfig = "bot_a:pruning=6,exploration=1.2,threads=4".parse().unwrap();
+        assert_eq!(config.threads, 4);
+        assert_eq!(config.exploration, 1.2);
+        assert_eq!(config.pruning_distance, Some(6));
+        assert_eq!(
+            config.to_string(),
+            "bot_a:threads=4,exploration=1.2,pruning=6"
+        );
+
+        assert_eq!(
+            "bot_a:threads=2".parse::<BotConfig>().unwrap().to_string(),
+            "bot_a"
+        );
+        assert!("random:threads=2".parse::<BotConfig>().is_err());
+        assert_eq!(
+            "duct:exploration=0.5"
+                .parse::<BotConfig>()
+                .unwrap()
+                .to_string(),
+            "duct:exploration=0.5"
+        );
+        assert!("duct:threads=2".parse::<BotConfig>().is_err());
+        assert_eq!(
+            "paranoid".parse::<BotConfig>().unwrap().kind,
+            BotKind::Paranoid
+        );
+        assert!("bot_b".parse::<BotConfig>().is_err());
+    }
+}
@@ -1,6 +1,8 @@
+use serde::{Deserialize, Serialize};
+
 use crate::Point;
 
-#[derive(Hash, Clone, Eq, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Hash, Clone, Eq, PartialEq, Debug)]
 pub enum Collision {
     Wall {
         id: usize,
@@ -23,6 +25,16 @@ pub enum Collision {
 }
 
 impl Collision {
+    /// Snake whose head collided.
+    pub fn id(&self) -> usize {
+        match *self {
+            Collision::Wall { id } => id,
+            Collision::SelfBody { id } => id,
+            Collision::OtherBody { id_1, .. } => id_1,
+            Collision::HeadToHead { id_1, .. } => id_1,
+        }
+    }
+
     pub fn causes_death(&self) -> bool {
         match *self {
             Collision::Wall { .. } => true,
@@ -0,0 +1,272 @@
+//! Decoupled UCT over the simultaneous moves of the snakes.
+//!
+//! Searching over joint moves gives up to 4^n children to every node, so with a few snakes
+//! the tree barely gets past its first levels. Here every snake keeps its own statistics on its
+//! own moves at each node and picks among them on its own, as if the others were part of the
+//! environment. Children are still reached by joint moves, but only the ones actually played
+//! get created.
+//!
+//! The tree is ours, so unlike the `mcts` crate its root can move down to the position the
+//! snakes actually reached, keeping what was searched below it.
+
+use mcts::GameState;
+
+use crate::engine::{Movement, MyEvaluator, SnakeGame};
+
+/// Exploration constant of the per snake UCB1, for rewards between 0 and 1.
+pub const DEFAULT_EXPLORATION: f64 = 0.7;
+/// Heuristic values this far apart make a clear difference in rewards.
+const REWARD_SCALE: f64 = 20.;
+
+/// Statistics of the moves of a snake at a node.
+#[derive(Debug)]
+struct SnakeStats {
+    moves: Vec<Movement>,
+    visits: Vec<u32>,
+    rewards: Vec<f64>,
+}
+
+impl SnakeStats {
+    fn new(moves: Vec<Movement>) -> Self {
+        SnakeStats {
+            visits: vec![0; moves.len()],
+            rewards: vec![0.; moves.len()],
+            moves,
+        }
+    }
+
+    /// UCB1 over the moves of the snake, the ones never tried first.
+    fn select(&self, node_visits: u32, exploration: f64) -> usize {
+        let ln_visits = ((node_visits + 1) as f64).ln();
+        let score = |i: usize| match self.visits[i] {
+            0 => f64::INFINITY,
+            n => self.rewards[i] / n as f64 + exploration * (ln_visits / n as f64).sqrt(),
+        };
+
+        (1..self.moves.len()).fold(0, |best, i| if score(i) > score(best) { i } else { best })
+    }
+
+    fn most_visited(&self) -> Option<Movement> {
+        (0..self.moves.len())
+            .max_by_key(|&i| self.visits[i])
+            .map(|i| self.moves[i])
+    }
+}
+
+#[derive(Debug)]
+struct Node {
+    visits: u32,
+    /// One entry per snake, none when the game is over.
+    snakes: Vec<SnakeStats>,
+    children: Vec<(Vec<Movement>, Node)>,
+}
+
+impl Node {
+    fn new(state: &SnakeGame) -> Self {
+        let nb_snakes = if state.board().is_game_over() {
+            0
+        } else {
+            state.board().snakes().len()
+        };
+
+        Node {
+            visits: 0,
+            snakes: (0..nb_snakes)
+                .map(|id| SnakeStats::new(state.available_moves_snake(id)))
+                .collect(),
+            children: vec![],
+        }
+    }
+
+    fn child_mut(&mut self, mov: &[Movement]) -> Option<&mut Node> {
+        self.children
+            .iter_mut()
+            .find(|(m, _)| m.as_slice() == mov)
+            .map(|(_, child)| child)
+    }
+
+    /// Plays down the tree from `state`, adding a node at the end, and gives the reward of
+    /// each snake.
+    fn playout(&mut self, state: &mut SnakeGame, exploration: f64) -> Vec<f64> {
+        if self.snakes.is_empty() {
+            return rewards(state);
+        }
+
+        let choices: Vec<usize> = self
+            .snakes
+            .iter()
+            .map(|s| s.select(self.visits, exploration))
+            .collect();
+        let mov: Vec<Movement> = self
+            .snakes
+            .iter()
+            .zip(&choices)
+            .map(|(s, &i)| s.moves[i])
+            .collect();
+
+        state.make_move(&mov);
+        let rewards = match self.child_mut(&mov) {
+            Some(child) => child.playout(state, exploration),
+            None => {
+                self.children.push((mov, Node::new(state)));
+                rewards(state)
+            }
+        };
+
+        self.visits += 1;
+        for ((stats, &i), &reward) in self.snakes.iter_mut().zip(&choices).zip(&rewards) {
+            stats.visits[i] += 1;
+            stats.rewards[i] += reward;
+        }
+
+        rewards
+    }
+}
+
+/// Reward of each snake in `state`, between 0 and 1.
+fn rewards(state: &SnakeGame) -> Vec<f64> {
+    let evaluation = MyEvaluator::evaluate(state);
+    (0..evaluation.len())
+        .map(|id| {
+            let reward = MyEvaluator::reward(&evaluation, id) as f64;
+            1. / (1. + (-reward / REWARD_SCALE).exp())
+        })
+        .collect()
+}
+
+pub struct Duct {
+    state: SnakeGame,
+    root: Node,
+    exploration: f64,
+}
+
+impl Duct {
+    pub fn new(game: SnakeGame, exploration: f64) -> Self {
+        Duct {
+            root: Node::new(&game),
+            state: game,
+            exploration,
+        }
+    }
+
+    pub fn playout(&mut self) {
+        let mut state = self.state.clone();
+        self.root.playout(&mut state, self.exploration);
+    }
+
+    pub fn playout_n(&mut self, n: u32) {
+        (0..n).for_each(|_| self.playout())
+    }
+
+    /// Number of playouts below the root.
+    pub fn visits(&self) -> u32 {
+        self.root.visits
+    }
+
+    /// Most played move of snake `id` at the root.
+    pub fn best_move(&self, id: usize) -> Option<Movement> {
+        self.root.snakes.get(id)?.most_visited()
+    }
+
+    /// Moves the root down to `game`, keeping what was searched from there. The search starts
+    /// over when `game` isn't one of the positions following the root, the result telling
+    /// whether the tree was kept.
+    pub fn advance(&mut self, game: &SnakeGame) -> bool {
+        let child = self.played_move(game).and_then(|mov| {
+            let i = self.root.children.iter().position(|(m, _)| *m == mov)?;
+            Some(self.root.children.swap_remove(i).1)
+        });
+
+        let kept = child.is_some();
+        self.root = child.unwrap_or_else(|| Node::new(game));
+        self.state = game.clone();
+        kept
+    }
+
+    /// Joint move leading from the root to `game`, told by where the heads went.
+    fn played_move(&self, game: &SnakeGame) -> Option<Vec<Movement>> {
+        let before = self.state.board();
+        let after = game.board();
+        if before.snakes().len() != after.snakes().len() || self.root.snakes.is_empty() {
+            return None;
+        }
+
+        before
+            .snakes()
+            .iter()
+            .zip(after.snakes())
+            .zip(&self.root.snakes)
+            .map(|((b, a), stats)| {
+                if b.is_dead() {
+                    return stats.moves.first().copied();
+                }
+                stats
+                    .moves
+                    .iter()
+                    .copied()
+                    .find(|&m| before.geometry().apply_mov(*b.head(), m) == *a.head())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::fixtures::dead_end as game;
+
+    #[test]
+    fn duct_avoids_dead_ends() {
+        let mut duct = Duct::new(game(), DEFAULT_EXPLORATION);
+        duct.playout_n(2000);
+        assert_eq!(duct.best_move(0), Some(Movement::Right));
+    }
+
+    #[test]
+    fn snakes_pick_their_moves_apart() {
+        let mut duct = Duct::new(game(), DEFAULT_EXPLORATION);
+        duct.playout_n(300);
+
+        // a statistic per move of each snake, every playout counted once by each of them
+        let root = &duct.root;
+        for (id, stats) in root.snakes.iter().enumerate() {
+            assert_eq!(stats.moves, game().available_moves_snake(id));
+            assert_eq!(stats.visits.iter().sum::<u32>(), root.visits);
+        }
+
+        // the joint moves of the children are made of the choices of each snake, only the
+        // played ones get a node
+        let played: u32 = root
+            .children
+            .iter()
+            .map(|(_, child)| child.visits + 1)
+            .sum();
+        assert!(played <= root.visits);
+        for (mov, _) in root.children.iter() {
+            for (stats, m) in root.snakes.iter().zip(mov) {
+                assert!(stats.visits[stats.moves.iter().position(|x| x == m).unwrap()] > 0);
+            }
+        }
+    }
+
+    #[test]
+    fn advance_keeps_the_subtree() {
+        let mut duct = Duct::new(game(), DEFAULT_EXPLORATION);
+        duct.playout_n(500);
+
+        let mov = duct
+            .root
+            .children
+            .iter()
+            .max_by_key(|(_, child)| child.visits)
+            .map(|(mov, _)| mov.clone())
+            .unwrap();
+        let mut next = game();
+        next.make_move(&mov);
+        assert!(duct.advance(&next));
+        assert!(duct.visits() > 0);
+
+        assert!(!duct.advance(&game()));
+        assert_eq!(duct.visits(), 0);
+    }
+}
@@ -0,0 +1,85 @@
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use super::duct::{Duct, DEFAULT_EXPLORATION};
+use super::player::BackgroundSearch;
+use super::Player;
+use crate::engine::{safe_move, Movement, SnakeGame};
+
+/// Searches with `Duct` in a thread of its own, keeping the tree from a turn to the next.
+pub struct DuctBot {
+    id: usize,
+    color: [f32; 4],
+    exploration: f64,
+    search: Option<BackgroundSearch<Duct>>,
+    /// Tree of the last search, moved down to the position of the next one.
+    tree: Option<Duct>,
+}
+
+impl DuctBot {
+    pub fn new(id: usize, color: [f32; 4]) -> Self {
+        DuctBot {
+            id,
+            color,
+            exploration: DEFAULT_EXPLORATION,
+            search: None,
+            tree: None,
+        }
+    }
+
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// Tree of the last search moved down to `game`, or a new one.
+    fn tree_of(&mut self, game: &SnakeGame) -> Duct {
+        match self.tree.take() {
+            Some(mut duct) => {
+                duct.advance(game);
+                duct
+            }
+            None => Duct::new(game.clone(), self.exploration),
+        }
+    }
+}
+
+impl Player for DuctBot {
+    fn think(&mut self, game: &SnakeGame) {
+        let (id, mut duct) = (self.id, self.tree_of(game));
+        let search = BackgroundSearch::start(game, id, move |stop| {
+            while !stop.load(Ordering::Relaxed) {
+                duct.playout();
+            }
+            let best_move = duct.best_move(id);
+            (duct, best_move)
+        });
+        self.search = Some(search);
+    }
+
+    fn next_move(&mut self) -> Movement {
+        // a tree lost to a panic is grown again from the next position
+        let (tree, best_move) = match self.search.take() {
+            Some(search) => search.stop(),
+            None => return Movement::Up,
+        };
+        self.tree = tree;
+        best_move
+    }
+
+    fn get_color(&self) -> [f32; 4] {
+        self.color
+    }
+
+    /// Grows the tree in the calling thread until the deadline.
+    fn search_until(&mut self, game: &SnakeGame, deadline: Instant) -> Movement {
+        let mut duct = self.tree_of(game);
+        while Instant::now() < deadline {
+            duct.playout();
+        }
+
+        let best_move = duct.best_move(self.id);
+        self.tree = Some(duct);
+        best_move.unwrap_or_else(|| safe_move(game, self.id))
+    }
+}
@@ -0,0 +1,20 @@
+//! Positions shared by the tests of the bots.
+
+use crate::engine::{Board, Point, Snake, SnakeGame};
+
+/// Snake of health 50 with the given body, tail first.
+pub(crate) fn snake(body: &[(i32, i32)]) -> Snake {
+    let body: Vec<Point> = body.iter().map(|&(x, y)| Point { x, y }).collect();
+    let head = *body.last().unwrap();
+    Snake::new_from(50, body.clone(), body.len(), head)
+}
+
+/// 5x5 game where snake 0, of three cells, has a pocket of two cells below its head and
+/// survives by going right only.
+pub(crate) fn dead_end() -> SnakeGame {
+    let snakes = vec![
+        snake(&[(0, 4), (0, 3), (0, 2)]),
+        snake(&[(1, 1), (1, 0), (2, 0), (3, 0)]),
+    ];
+    SnakeGame::new(Board::new_from(5, 5, snakes, &[], &[]))
+}
@@ -1,16 +1,12 @@
-use crate::engine::{Board, Movement};
-use itertools::*;
-use mcts::transposition_table::TranspositionHash;
-use mcts::GameState;
-use std::{
-    collections::hash_map::DefaultHasher,
-    hash::{Hash, Hasher},
-};
+use super::safety::predicted_move;
+use crate::engine::{Board, Movement, Point, Snake, Undo};
 
 #[derive(Clone, Debug)]
 pub struct SnakeGame {
     current_player: usize,
     board: Board,
+    food_sampling: bool,
+    pruning_distance: Option<i32>,
 }
 
 impl SnakeGame {
@@ -18,6 +14,8 @@ impl SnakeGame {
         SnakeGame {
             current_player: 0,
             board,
+            food_sampling: false,
+            pruning_distance: None,
         }
     }
 
@@ -26,30 +24,86 @@ impl SnakeGame {
         self.current_player = id
     }
 
-    pub fn available_moves_snake(&self, id: usize) -> Vec<Movement> {
+    #[inline]
+    pub fn current_player(&self) -> usize {
+        self.current_player
+    }
+
+    /// When enabled, simulated moves spawn food following the ruleset's probabilities instead
+    /// of never spawning any. Each playout draws its own spawns, so the statistics of a node
+    /// average over the possible futures. The spawns come from the generator of the thread,
+    /// the seed of the board keeps replaying the real game, and hazards don't move.
+    pub fn set_food_sampling(&mut self, enabled: bool) {
+        self.food_sampling = enabled
+    }
+
+    #[inline]
+    pub fn food_sampling(&self) -> bool {
+        self.food_sampling
+    }
+
+    /// Snakes whose head is further than `distance` moves from the head of the current player
+    /// can't get in its way soon. The search then only looks into a single predicted move for
+    /// them, instead of multiplying its branches by all of their moves. Distances are counted
+    /// in moves on an empty board, walls of bodies aside.
+    pub fn set_pruning_distance(&mut self, distance: Option<i32>) {
+        self.pruning_distance = distance
+    }
+
+    #[inline]
+    pub fn pruning_distance(&self) -> Option<i32> {
+        self.pruning_distance
+    }
+
+    /// Moves of snake `id` the search looks into, see `set_pruning_distance`.
+    pub fn searched_moves(&self, id: usize) -> Vec<Movement> {
+        let snakes = self.board.snakes();
+        let is_far = |distance| {
+            let player = &snakes[self.current_player];
+            let geometry = self.board.geometry();
+            id != self.current_player
+                && !snakes[id].is_dead()
+                && geometry.distance(*player.head(), *snakes[id].head()) > distance
+        };
+
+        match self.pruning_distance {
+            Some(distance) if is_far(distance) => vec![predicted_move(self, id)],
+            _ => self.available_moves_snake(id),
+        }
+    }
+
+    /// Moves of the snake that don't kill it right away, empty when every move does.
+    pub fn non_fatal_moves(&self, id: usize) -> Vec<Movement> {
         let s = &self.board.snakes()[id];
+        if s.is_dead() {
+            return vec![];
+        }
+
         let head = s.head();
         let matrice = self.board.matrice();
-        let available_moves = if s.is_dead() {
-            vec![]
-        } else {
-            [
-                Movement::Down,
-                Movement::Left,
-                Movement::Up,
-                Movement::Right,
-            ]
-            .iter()
-            .filter_map(|&m| -> Option<Movement> {
-                let new_position = head.apply_mov(m);
-                if self.board.is_outside(new_position) || matrice.get(new_position).is_some() {
-                    None
-                } else {
-                    Some(m)
-                }
-            })
-            .collect()
-        };
+        let occupied = matrice.occupied();
+        [
+            Movement::Down,
+            Movement::Left,
+            Movement::Up,
+            Movement::Right,
+        ]
+        .iter()
+        .filter_map(|&m| -> Option<Movement> {
+            let new_position = matrice.neighbour(*head, m)?;
+            let on_body = occupied.contains(matrice.layout().index(new_position));
+            if on_body || self.is_lethal_hazard(s, new_position) {
+                None
+            } else {
+                Some(m)
+            }
+        })
+        .collect()
+    }
+
+    /// Like `non_fatal_moves`, with `Up` when there is none so that the snake still plays.
+    pub fn available_moves_snake(&self, id: usize) -> Vec<Movement> {
+        let available_moves = self.non_fatal_moves(id);
 
         if available_moves.is_empty() {
             vec![Movement::Up]
@@ -58,6 +112,13 @@ impl SnakeGame {
         }
     }
 
+    /// Entering a hazard without eating would starve the snake to death.
+    fn is_lethal_hazard(&self, s: &Snake, p: Point) -> bool {
+        self.board.is_hazard(p)
+            && !self.board.is_food(p)
+            && s.health() <= 1 + self.board.hazard_damage()
+    }
+
     pub fn board(&self) -> &Board {
         &self.board
     }
@@ -65,35 +126,104 @@ impl SnakeGame {
     pub fn step(&mut self, mov: Vec<Movement>) {
         self.board.step(mov, false);
     }
-}
 
-impl TranspositionHash for SnakeGame {
-    fn hash(&self) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        self.board.hash(&mut hasher);
-        hasher.finish()
+    /// Plays `mov` without spawning food, to be taken back with `take_back`. See
+    /// `Board::make_move`.
+    pub fn play(&mut self, mov: &[Movement], undo: &mut Undo) {
+        self.board.make_move(mov, undo);
+    }
+
+    pub fn take_back(&mut self, undo: &Undo) {
+        self.board.unmake_move(undo);
     }
 }
 
-impl GameState for SnakeGame {
-    type Player = usize;
-    type Move = Vec<Movement>;
-    type MoveList = Vec<Vec<Movement>>;
+#[cfg(feature = "search")]
+mod search {
+    use super::SnakeGame;
+    use crate::engine::Movement;
+    use itertools::*;
+    use mcts::transposition_table::TranspositionHash;
+    use mcts::GameState;
 
-    fn current_player(&self) -> Self::Player {
-        self.current_player
+    impl TranspositionHash for SnakeGame {
+        fn hash(&self) -> u64 {
+            self.board.zobrist()
+        }
     }
 
-    fn available_moves(&self) -> Self::MoveList {
-        let s = (0..self.board().snakes().len())
-            .map(|id| self.available_moves_snake(id))
-            .multi_cartesian_product()
-            .collect();
+    impl GameState for SnakeGame {
+        type Player = usize;
+        type Move = Vec<Movement>;
+        type MoveList = Vec<Vec<Movement>>;
+
+        fn current_player(&self) -> Self::Player {
+            SnakeGame::current_player(self)
+        }
+
+        fn available_moves(&self) -> Self::MoveList {
+            if self.board.is_game_over() {
+                return vec![];
+            }
+
+            let s = (0..self.board().snakes().len())
+                .map(|id| self.searched_moves(id))
+                .multi_cartesian_product()
+                .collect();
 
-        s
+            s
+        }
+
+        fn make_move(&mut self, mov: &Self::Move) {
+            self.board.step(mov.clone(), true);
+            if self.food_sampling() {
+                self.board.spawn_food(&mut rand::thread_rng());
+            }
+        }
     }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn far_snakes_get_a_single_move() {
+        let snakes = vec![
+            Snake::new(Point { x: 1, y: 1 }),
+            Snake::new(Point { x: 2, y: 3 }),
+            Snake::new(Point { x: 9, y: 9 }),
+        ];
+        let mut game = SnakeGame::new(Board::new_from(11, 11, snakes, &[], &[]));
+
+        assert_eq!(game.searched_moves(2).len(), 4);
+
+        game.set_pruning_distance(Some(4));
+        assert_eq!(game.searched_moves(0).len(), 4);
+        assert_eq!(game.searched_moves(1).len(), 4);
+        assert_eq!(game.searched_moves(2).len(), 1);
+    }
+
+    #[cfg(feature = "search")]
+    #[test]
+    fn sampled_food_leaves_the_seed_alone() {
+        use mcts::GameState;
+        use rand::Rng;
+
+        let snakes = vec![
+            Snake::new(Point { x: 1, y: 1 }),
+            Snake::new(Point { x: 5, y: 5 }),
+        ];
+        let mut root = SnakeGame::new(Board::new_from(7, 7, snakes, &[], &[]).with_seed(9));
+        root.set_food_sampling(true);
+
+        let mut playout = root.clone();
+        GameState::make_move(&mut playout, &vec![Movement::Up, Movement::Down]);
 
-    fn make_move(&mut self, mov: &Self::Move) {
-        self.board.step(mov.clone(), true);
+        // no food left, the minimum is spawned right away
+        assert_eq!(playout.board().food().len(), 1);
+        assert_eq!(playout.board().seed(), root.board().seed());
+        let (mut played, mut unplayed) = (playout.board().clone(), root.board().clone());
+        assert_eq!(played.rng().gen::<u64>(), unplayed.rng().gen::<u64>());
     }
 }
@@ -1,24 +1,30 @@
-use crate::engine::{board::SnakeId, Point, Snake};
+use crate::engine::{
+    bitboard::{BitBoard, Layout},
+    board::SnakeId,
+    zobrist, Geometry, Movement, Point, Snake,
+};
 use ndarray::Array2;
-
-use super::Collision;
+use std::mem;
 
 pub(crate) type CellValue = u8;
-pub type Displacement = (SnakeId, (Option<Point>, Point));
 
 #[derive(Hash, Clone, Eq, PartialEq, Debug)]
 pub struct Matrice {
-    height: usize,
-    width: usize,
+    layout: Layout,
     array: Array2<CellValue>,
+    /// Cells with a snake, as in `array`.
+    occupied: BitBoard,
+    /// Zobrist keys of the bodies, see `Board::zobrist`.
+    zobrist: u64,
 }
 
 impl Matrice {
-    pub fn new(snakes: &[Snake], height: usize, width: usize) -> Self {
+    pub fn new(snakes: &[Snake], geometry: Geometry) -> Self {
         let mut matrice = Matrice {
-            height,
-            width,
-            array: Array2::zeros((height, width)),
+            layout: Layout::new(geometry),
+            array: Array2::zeros((geometry.height as usize, geometry.width as usize)),
+            occupied: BitBoard::default(),
+            zobrist: 0,
         };
 
         snakes.iter().enumerate().for_each(|(id, snake)| {
@@ -34,42 +40,12 @@ impl Matrice {
 
 // updaters
 impl Matrice {
-    pub fn update(&mut self, displacements: Vec<Displacement>, collisions: &[Collision]) {
-        // fix collisions overrides
-        collisions
-            .iter()
-            .filter_map(|c| match c {
-                Collision::OtherBody { id_2, loc, .. } => Some((id_2, loc)),
-                Collision::HeadToHead {
-                    src_length,
-                    dst_length,
-                    id_1,
-                    id_2,
-                    loc,
-                } => match src_length {
-                    _ if src_length < dst_length => Some((id_2, loc)),
-                    _ if src_length > dst_length => Some((id_1, loc)),
-                    _ => None,
-                },
-                _ => None,
-            })
-            .for_each(|(&id, &p)| self.mark_snake(id as u8, p));
-
-        // apply displacements
-        displacements.iter().for_each(|&(idx, (tail, to))| {
-            if let Some(tail) = tail {
-                self.mark_empty(tail);
-            }
-
-            self.mark_snake(idx, to);
-        })
-    }
-
-    pub fn remove_points<'a>(&mut self, points: impl Iterator<Item = &'a Point>) {
-        let (h, w) = (self.height, self.height);
-        points
-            .filter(|&p| p.x >= 0 && p.x < w as i32 && p.y >= 0 && p.y < h as i32)
-            .for_each(|p| self.mark_empty(*p));
+    pub(crate) fn set_geometry(&mut self, geometry: Geometry) {
+        debug_assert_eq!(
+            (self.geometry().width, self.geometry().height),
+            (geometry.width, geometry.height)
+        );
+        self.layout = Layout::new(geometry);
     }
 }
 
@@ -79,6 +55,31 @@ impl Matrice {
         &self.array
     }
 
+    #[inline]
+    pub fn geometry(&self) -> Geometry {
+        self.layout.geometry()
+    }
+
+    #[inline]
+    pub(crate) fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    #[inline]
+    pub(crate) fn occupied(&self) -> &BitBoard {
+        &self.occupied
+    }
+
+    #[inline]
+    pub fn zobrist(&self) -> u64 {
+        self.zobrist
+    }
+
+    #[inline]
+    pub fn neighbour(&self, p: Point, mov: Movement) -> Option<Point> {
+        self.geometry().neighbour(p, mov)
+    }
+
     #[inline]
     pub fn get(&self, p: Point) -> Option<CellValue> {
         match self.array[[p.y as usize, p.x as usize]] {
@@ -87,9 +88,27 @@ impl Matrice {
         }
     }
 
+    /// Raw value of the cell of `p`, to be put back with `set`.
     #[inline]
-    fn set(&mut self, p: Point, v: CellValue) {
-        self.array[[p.y as usize, p.x as usize]] = v;
+    pub(crate) fn cell(&self, p: Point) -> CellValue {
+        self.array[[p.y as usize, p.x as usize]]
+    }
+
+    #[inline]
+    pub(crate) fn set(&mut self, p: Point, v: CellValue) {
+        let cell = &mut self.array[[p.y as usize, p.x as usize]];
+        let previous = mem::replace(cell, v);
+
+        let i = self.layout.index(p);
+        if previous != 0 {
+            self.zobrist ^= zobrist::body(previous as usize - 1, i);
+        }
+        if v == 0 {
+            self.occupied.remove(i);
+        } else {
+            self.occupied.insert(i);
+            self.zobrist ^= zobrist::body(v as usize - 1, i);
+        }
     }
 }
 
@@ -99,9 +118,41 @@ impl Matrice {
     pub fn mark_snake(&mut self, v: SnakeId, p: Point) {
         self.set(p, (v + 1) as CellValue);
     }
+}
 
-    #[inline]
-    fn mark_empty(&mut self, p: Point) {
-        self.set(p, 0);
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::Board;
+
+    fn snake(body: &[(i32, i32)]) -> Snake {
+        let body: Vec<Point> = body.iter().map(|&(x, y)| Point { x, y }).collect();
+        let head = *body.last().unwrap();
+        Snake::new_from(50, body.clone(), 3.max(body.len()), head)
+    }
+
+    #[test]
+    fn bodies_keep_their_cells_through_the_moves() {
+        let snakes = vec![
+            // goes into the cell its tail leaves
+            snake(&[(1, 1), (1, 2), (2, 2), (2, 1)]),
+            // goes into the cell the tail of the next one leaves
+            snake(&[(7, 3), (7, 2), (7, 1), (6, 1)]),
+            snake(&[(5, 1), (5, 2), (5, 3)]),
+            // ate on the last turn, its tail is stacked
+            snake(&[(1, 5), (1, 5), (1, 6)]),
+        ];
+        let mut board = Board::new_from(9, 9, snakes, &[], &[]);
+        board.step(
+            vec![Movement::Left, Movement::Left, Movement::Up, Movement::Up],
+            true,
+        );
+
+        assert_eq!(board.alive_snakes().count(), 4);
+        let matrice = board.matrice();
+        assert_eq!(matrice.get(Point { x: 1, y: 1 }), Some(0));
+        assert_eq!(matrice.get(Point { x: 5, y: 1 }), Some(1));
+        assert_eq!(matrice.get(Point { x: 1, y: 5 }), Some(3));
+        assert_eq!(matrice, &Matrice::new(board.snakes(), board.geometry()));
     }
 }
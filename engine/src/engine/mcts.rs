@@ -1,64 +1,36 @@
 use crate::engine::{Collision, DEFAULT_SNAKE_HEALTH};
 
-use super::matrice::CellValue;
-use super::{Movement, Snake, SnakeGame};
-use mcts::{tree_policy::UCTPolicy, Evaluator, MCTS};
-use ndarray::{Array1, Array2};
-use std::{collections::VecDeque, iter::FromIterator};
+use super::bitboard::BitBoard;
+use super::warm_start::{Prior, Priors};
+use super::{Board, Movement, SnakeGame};
+use mcts::transposition_table::ApproxTable;
+use mcts::tree_policy::{PolicyRng, TreePolicy};
+use mcts::{Evaluator, MoveInfo, SearchHandle, MCTS};
+use ndarray::Array1;
 use std::{usize, vec};
 
-pub struct MyEvaluator;
+const MOVES: [Movement; 4] = [
+    Movement::Down,
+    Movement::Up,
+    Movement::Right,
+    Movement::Left,
+];
 
-impl MyEvaluator {
-    fn expand_conquer_array(mut array: Array2<CellValue>, snakes: &[Snake]) -> Array1<i64> {
-        let (height, width) = (array.shape()[0] as i32, array.shape()[1] as i32);
-        let mut lengths: Array1<i64> = snakes.iter().map(|s| s.body().len() as i64).collect();
-
-        let mut q = VecDeque::from_iter(snakes.iter().enumerate().map(|(id, s)| (id, *s.head())));
-
-        while !q.is_empty() {
-            let (id, pos) = q.pop_front().unwrap();
-            lengths[id] += 1;
-
-            let new_points = [
-                Movement::Down,
-                Movement::Up,
-                Movement::Right,
-                Movement::Left,
-            ]
-            .iter()
-            .filter_map(|&m| {
-                let p = pos.apply_mov(m);
-                if (p.x < 0 || p.x >= width || p.y < 0 || p.y >= height)
-                    || array[[p.y as usize, p.x as usize]] != 0
-                {
-                    None
-                } else {
-                    array[[p.y as usize, p.x as usize]] = (id + 1) as CellValue;
-                    Some((id, p))
-                }
-            });
-
-            q.extend(new_points.into_iter());
-        }
-
-        lengths
-    }
+/// Evaluates the positions, and gives their moves the priors of the previous search.
+#[derive(Default)]
+pub struct MyEvaluator {
+    priors: Priors,
 }
 
-impl Evaluator<MyMCTS> for MyEvaluator {
-    type StateEvaluation = Array1<i64>;
+impl MyEvaluator {
+    pub fn new(priors: Priors) -> Self {
+        MyEvaluator { priors }
+    }
 
-    fn evaluate_new_state(
-        &self,
-        state: &SnakeGame,
-        moves: &Vec<Vec<Movement>>,
-        _: Option<mcts::SearchHandle<MyMCTS>>,
-    ) -> (Vec<()>, Self::StateEvaluation) {
+    /// Heuristic value of the position for each snake.
+    pub(crate) fn evaluate(state: &SnakeGame) -> Array1<i64> {
         let snakes = state.board().snakes();
 
-        let array = state.board().matrice().array().clone();
-
         let avg_len : i64 = (state.board()
             .alive_snakes()
             .map(|(_, s)| s.length() as f32)
@@ -74,7 +46,7 @@ impl Evaluator<MyMCTS> for MyEvaluator {
             .map(|s| (s.health() as i64  - (DEFAULT_SNAKE_HEALTH as f32 / 2.).ceil() as i64))
             .collect();
 
-        let p_area = Self::expand_conquer_array(array, snakes);
+        let p_area = Self::expand_conquer(state.board());
         let p_death: Array1<i64> = snakes
             .iter()
             .map(|s| if s.is_dead() { -100 } else { 0 })
@@ -105,9 +77,76 @@ impl Evaluator<MyMCTS> for MyEvaluator {
                 p_collisions[id] += score;
             });
 
-        let p_total = p_area + p_collisions + p_death + p_health + p_diff_len_with_mean;
+        p_area + p_collisions + p_death + p_health + p_diff_len_with_mean
+    }
+
+    /// Value of `evaluation` for `player`, relative to the other snakes.
+    pub(crate) fn reward(evaluation: &Array1<i64>, player: usize) -> i64 {
+        let score_player = evaluation[player];
+        let score_others = (evaluation.sum() - score_player) as f64;
 
-        (vec![(); moves.len()], p_total)
+        score_player - (0.5 * score_others) as i64
+    }
+
+    /// Size of the body of each snake, plus the cells it reaches before the others. Hazard
+    /// cells are conquered like any other cell but aren't worth any area.
+    fn expand_conquer(board: &Board) -> Array1<i64> {
+        let (matrice, snakes) = (board.matrice(), board.snakes());
+        let (layout, geometry) = (matrice.layout(), matrice.geometry());
+        let mut free = layout.cells().difference(matrice.occupied());
+        let mut lengths: Array1<i64> = snakes.iter().map(|s| s.body().len() as i64).collect();
+
+        // the heads go first, a step at a time from there, the snakes taking turns on each step
+        let mut next_cells: Vec<BitBoard> = snakes
+            .iter()
+            .enumerate()
+            .map(|(id, s)| {
+                let head = *s.head();
+                if !board.is_hazard(head) {
+                    lengths[id] += 1;
+                }
+
+                // heads may have left the board on a wall
+                let mut cells = BitBoard::default();
+                MOVES
+                    .iter()
+                    .filter_map(|&m| geometry.neighbour(head, m))
+                    .for_each(|p| cells.insert(layout.index(p)));
+                cells
+            })
+            .collect();
+
+        let mut conquering = true;
+        while conquering {
+            conquering = false;
+            for (id, cells) in next_cells.iter_mut().enumerate() {
+                if cells.is_empty() {
+                    continue;
+                }
+
+                let conquered = *cells & free;
+                free = free.difference(&conquered);
+                lengths[id] += conquered.difference(board.hazard_cells()).len() as i64;
+
+                conquering |= !conquered.is_empty();
+                *cells = layout.neighbours(&conquered);
+            }
+        }
+
+        lengths
+    }
+}
+
+impl Evaluator<MyMCTS> for MyEvaluator {
+    type StateEvaluation = Array1<i64>;
+
+    fn evaluate_new_state(
+        &self,
+        state: &SnakeGame,
+        moves: &Vec<Vec<Movement>>,
+        _: Option<mcts::SearchHandle<MyMCTS>>,
+    ) -> (Vec<Prior>, Self::StateEvaluation) {
+        (self.priors.get(state, moves), Self::evaluate(state))
     }
 
     fn evaluate_existing_state(
@@ -124,13 +163,69 @@ impl Evaluator<MyMCTS> for MyEvaluator {
         evaluation: &Self::StateEvaluation,
         player: &mcts::Player<MyMCTS>,
     ) -> i64 {
-        let score_player = evaluation[*player];
-        let score_others = (evaluation.sum() - score_player) as f64;
+        Self::reward(evaluation, *player)
+    }
+}
 
-        score_player - (0.5 * score_others) as i64
+/// UCT counting the priors of a move as visits and rewards it already had.
+#[derive(Clone, Debug)]
+pub struct PriorUCTPolicy {
+    exploration_constant: f64,
+}
+
+impl PriorUCTPolicy {
+    pub fn new(exploration_constant: f64) -> Self {
+        assert!(
+            exploration_constant > 0.0,
+            "exploration constant is {} (must be positive)",
+            exploration_constant
+        );
+        PriorUCTPolicy {
+            exploration_constant,
+        }
+    }
+}
+
+impl TreePolicy<MyMCTS> for PriorUCTPolicy {
+    type MoveEvaluation = Prior;
+    type ThreadLocalData = PolicyRng;
+
+    fn choose_child<'a, MoveIter>(
+        &self,
+        moves: MoveIter,
+        mut handle: SearchHandle<MyMCTS>,
+    ) -> &'a MoveInfo<MyMCTS>
+    where
+        MoveIter: Iterator<Item = &'a MoveInfo<MyMCTS>> + Clone,
+    {
+        let total_visits = moves
+            .clone()
+            .map(|m| m.visits() + m.move_evaluation().visits)
+            .sum::<u64>();
+        let ln_adjusted_total = ((total_visits + 1) as f64).ln();
+
+        handle
+            .thread_data()
+            .policy_data
+            .select_by_key(moves, |m| {
+                let prior = m.move_evaluation();
+                let visits = m.visits() + prior.visits;
+                if visits == 0 {
+                    f64::INFINITY
+                } else {
+                    let explore_term = 2.0 * (ln_adjusted_total / visits as f64).sqrt();
+                    let mean_action_value =
+                        (m.sum_rewards() + prior.sum_rewards) as f64 / visits as f64;
+                    self.exploration_constant * explore_term + mean_action_value
+                }
+            })
+            .unwrap()
     }
 }
 
+/// The search of the bots, with a transposition table keyed on `Board::zobrist`. That hash
+/// counts health by tens, so positions differing by a few points of health share a node and
+/// its statistics.
 #[derive(Default)]
 pub struct MyMCTS;
 
@@ -139,9 +234,8 @@ impl MCTS for MyMCTS {
     type Eval = MyEvaluator;
     type NodeData = ();
     type ExtraThreadData = ();
-    type TreePolicy = UCTPolicy;
-    // type TranspositionTable = ApproxTable<Self>;
-    type TranspositionTable = ();
+    type TreePolicy = PriorUCTPolicy;
+    type TranspositionTable = ApproxTable<Self>;
 
     fn virtual_loss(&self) -> i64 {
         0
@@ -181,3 +275,75 @@ impl MCTS for MyMCTS {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::matrice::CellValue;
+    use crate::engine::{ruleset_from_name, Outcome, Point, Settings, Snake};
+    use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+    use std::collections::VecDeque;
+
+    /// The conquest a cell at a time, as done before the bitboards.
+    fn expand_conquer_by_cells(board: &Board) -> Array1<i64> {
+        let (mut array, geometry) = (board.matrice().array().clone(), board.geometry());
+        let snakes = board.snakes();
+        let mut lengths: Array1<i64> = snakes.iter().map(|s| s.body().len() as i64).collect();
+
+        let mut q: VecDeque<(usize, Point)> = snakes
+            .iter()
+            .enumerate()
+            .map(|(id, s)| (id, *s.head()))
+            .collect();
+        while let Some((id, pos)) = q.pop_front() {
+            if !board.hazards().contains(&pos) {
+                lengths[id] += 1;
+            }
+
+            for p in MOVES.iter().filter_map(|&m| geometry.neighbour(pos, m)) {
+                if array[[p.y as usize, p.x as usize]] == 0 {
+                    array[[p.y as usize, p.x as usize]] = (id + 1) as CellValue;
+                    q.push_back((id, p));
+                }
+            }
+        }
+
+        lengths
+    }
+
+    #[test]
+    fn conquest_matches_the_one_by_cells() {
+        let settings = Settings {
+            shrink_every_n_turns: 5,
+            ..Settings::default()
+        };
+        let mut rng = StdRng::seed_from_u64(11);
+
+        for seed in 0..10 {
+            let snakes = vec![
+                Snake::new(Point { x: 1, y: 1 }),
+                Snake::new(Point { x: 9, y: 9 }),
+                Snake::new(Point { x: 1, y: 9 }),
+                Snake::new(Point { x: 9, y: 1 }),
+            ];
+            let board = Board::new(11, 11, snakes)
+                .with_ruleset(ruleset_from_name("royale"))
+                .with_settings(settings)
+                .with_seed(seed);
+            let mut game = SnakeGame::new(board);
+
+            while game.board().outcome() == Outcome::Ongoing {
+                let board = game.board();
+                assert_eq!(
+                    MyEvaluator::expand_conquer(board),
+                    expand_conquer_by_cells(board)
+                );
+
+                let moves = (0..board.snakes().len())
+                    .map(|id| *game.available_moves_snake(id).choose(&mut rng).unwrap())
+                    .collect();
+                game.step(moves);
+            }
+        }
+    }
+}
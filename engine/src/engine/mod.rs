@@ -1,24 +1,77 @@
 use serde::{Deserialize, Serialize};
 
+pub mod api;
+mod arena;
+mod bitboard;
 mod board;
 mod collision;
+#[cfg(test)]
+mod fixtures;
 mod game;
 mod matrice;
+#[cfg(feature = "search")]
 mod mcts;
 mod point;
+mod render;
+mod replay;
 mod reward;
+mod ruleset;
+mod safety;
 mod snake;
+mod state;
+#[cfg(feature = "search")]
+mod tournament;
+#[cfg(feature = "search")]
 mod bot_a;
+#[cfg(feature = "search")]
+mod bot_config;
+#[cfg(feature = "search")]
+mod duct;
+#[cfg(feature = "search")]
+mod duct_bot;
+#[cfg(feature = "search")]
+mod paranoid;
+#[cfg(feature = "search")]
+mod paranoid_bot;
+#[cfg(feature = "search")]
+mod warm_start;
 mod player;
+mod random_bot;
+mod zobrist;
 
-pub use self::mcts::{MyEvaluator, MyMCTS};
-pub use board::Board;
+#[cfg(feature = "search")]
+pub use self::mcts::{MyEvaluator, MyMCTS, PriorUCTPolicy};
+pub use arena::{Arena, ArenaStats, Death, DeathCause, GameResult, PlayerFactory, PlayerStats};
+pub use board::{Board, Outcome, Undo};
 pub use collision::Collision;
 pub use game::SnakeGame;
-pub use point::Point;
+pub use point::{Geometry, Point};
+pub use render::Tile;
+pub use replay::{Frame, Recorder, Replay};
+pub use ruleset::{ruleset_from_name, Ruleset, Settings};
+pub use safety::safe_move;
 pub use snake::{Snake, DEFAULT_SNAKE_HEALTH};
+pub use state::{BoardState, STATE_FORMAT_VERSION};
+#[cfg(feature = "search")]
+pub use tournament::{
+    duplicate_bot, expected_score, Entry, Format, Leaderboard, MatchResult, Tournament,
+};
+#[cfg(feature = "search")]
 pub use bot_a::BotA;
+#[cfg(feature = "search")]
+pub use bot_config::{BotConfig, BotKind};
+#[cfg(feature = "search")]
+pub use duct::Duct;
+#[cfg(feature = "search")]
+pub use duct_bot::DuctBot;
+#[cfg(feature = "search")]
+pub use paranoid::Paranoid;
+#[cfg(feature = "search")]
+pub use paranoid_bot::ParanoidBot;
+#[cfg(feature = "search")]
+pub use warm_start::SearchMemory;
 pub use player::Player;
+pub use random_bot::RandomBot;
 
 use rand::{
     distributions::{Distribution, Standard},
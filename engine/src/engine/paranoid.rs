@@ -0,0 +1,367 @@
+//! Paranoid alpha-beta over the turns of the game.
+//!
+//! Snakes move at the same time, which a tree of alternating moves can't show. The paranoid
+//! view has us move first and the other snakes answer together, knowing our move and playing
+//! against us: the value of a move is what we get when the worst happens. Positions are
+//! valued with the heuristics of `MyEvaluator`.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use itertools::Itertools;
+use mcts::transposition_table::TranspositionHash;
+
+use crate::engine::{Movement, MyEvaluator, Outcome, SnakeGame, Undo};
+
+/// Value of a won game, lowered by the number of turns to win it.
+const WIN: i64 = 1_000_000;
+/// Value of a game ending with every snake dead.
+const DRAW: i64 = -WIN / 2;
+/// Searches stop at this depth, in turns.
+pub const MAX_DEPTH: u32 = 64;
+/// The tables are cleared past this many entries.
+const TABLE_SIZE: usize = 1 << 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    /// `verification_key` of the position, the Zobrist hash keying the table counts health by
+    /// tens only.
+    check: u64,
+    depth: u32,
+    /// Won and lost games count the turns from this position, not from the root.
+    value: i64,
+    bound: Bound,
+    best_move: Movement,
+}
+
+/// Hash of what the Zobrist hash leaves out: the exact health, length and body of the snakes.
+fn verification_key(game: &SnakeGame) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for snake in game.board().snakes() {
+        (snake.health(), snake.length()).hash(&mut hasher);
+        snake.body().iter().for_each(|p| p.hash(&mut hasher));
+    }
+    hasher.finish()
+}
+
+/// `value` found `ply` turns from the root, as stored for its position.
+fn to_table(value: i64, ply: u32) -> i64 {
+    match value {
+        v if v >= WIN - MAX_DEPTH as i64 => v + ply as i64,
+        v if v <= -WIN + MAX_DEPTH as i64 => v - ply as i64,
+        v => v,
+    }
+}
+
+/// Stored `value` of a position reached `ply` turns from the root.
+fn from_table(value: i64, ply: u32) -> i64 {
+    match value {
+        v if v >= WIN - MAX_DEPTH as i64 => v - ply as i64,
+        v if v <= -WIN + MAX_DEPTH as i64 => v + ply as i64,
+        v => v,
+    }
+}
+
+/// Alpha-beta search for snake `id`, keeping its tables from a search to the next.
+pub struct Paranoid {
+    id: usize,
+    /// Positions already searched, at the turn of our move.
+    table: HashMap<u64, Entry>,
+    /// Best answer of the other snakes found to our move from a position, tried first.
+    refutations: HashMap<(u64, Movement), Vec<Movement>>,
+    /// Records of the turns played ahead, one per depth, reused by every branch.
+    undos: Vec<Undo>,
+}
+
+impl Paranoid {
+    pub fn new(id: usize) -> Self {
+        Paranoid {
+            id,
+            table: HashMap::new(),
+            refutations: HashMap::new(),
+            undos: vec![],
+        }
+    }
+
+    /// Searches `game` one turn deeper at a time until `stop` is set, giving the best move of
+    /// the deepest search completed.
+    pub fn iterative_deepening(&mut self, game: &SnakeGame, stop: &AtomicBool) -> Option<Movement> {
+        if self.table.len() > TABLE_SIZE || self.refutations.len() > TABLE_SIZE {
+            self.table.clear();
+            self.refutations.clear();
+        }
+
+        let mut game = game.clone();
+        game.set_player(self.id);
+        game.set_food_sampling(false);
+
+        let mut best_move = None;
+        for depth in 1..=MAX_DEPTH {
+            let value = match self.max_node(&mut game, depth, 0, -WIN, WIN, stop) {
+                Some(value) => value,
+                None => break,
+            };
+
+            best_move = self.entry(&game).map(|e| e.best_move).or(best_move);
+
+            // the end of the game is in sight, deeper searches won't tell more
+            if value.abs() >= WIN - MAX_DEPTH as i64 {
+                break;
+            }
+        }
+
+        best_move
+    }
+
+    /// Entry of the position of `game`, if it is the one stored under its hash.
+    fn entry(&self, game: &SnakeGame) -> Option<Entry> {
+        let check = verification_key(game);
+        self.table
+            .get(&TranspositionHash::hash(game))
+            .filter(|e| e.check == check)
+            .copied()
+    }
+
+    /// Value of a game over, `ply` turns from the root.
+    fn terminal_value(&self, game: &SnakeGame, ply: u32) -> Option<i64> {
+        let board = game.board();
+        match board.outcome() {
+            Outcome::Win { id } if id == self.id => Some(WIN - ply as i64),
+            Outcome::Draw => Some(DRAW),
+            _ if board.snakes()[self.id].is_dead() => Some(-WIN + ply as i64),
+            _ => None,
+        }
+    }
+
+    fn max_node(
+        &mut self,
+        game: &mut SnakeGame,
+        depth: u32,
+        ply: u32,
+        mut alpha: i64,
+        beta: i64,
+        stop: &AtomicBool,
+    ) -> Option<i64> {
+        if stop.load(Ordering::Relaxed) {
+            return None;
+        }
+        if let Some(value) = self.terminal_value(game, ply) {
+            return Some(value);
+        }
+        if depth == 0 {
+            return Some(MyEvaluator::reward(&MyEvaluator::evaluate(game), self.id));
+        }
+
+        let (hash, check) = (TranspositionHash::hash(game), verification_key(game));
+        let entry = self.table.get(&hash).filter(|e| e.check == check).copied();
+        if let Some(entry) = entry.filter(|e| e.depth >= depth) {
+            let value = from_table(entry.value, ply);
+            match entry.bound {
+                Bound::Exact => return Some(value),
+                Bound::Lower if value >= beta => return Some(value),
+                Bound::Upper if value <= alpha => return Some(value),
+                _ => {}
+            }
+        }
+
+        // the best move of a shallower search first
+        let mut moves = game.available_moves_snake(self.id);
+        if let Some(i) = entry.and_then(|e| moves.iter().position(|&m| m == e.best_move)) {
+            moves.swap(0, i);
+        }
+
+        let original_alpha = alpha;
+        let mut best = (i64::MIN, moves[0]);
+        for mov in moves {
+            let value = self.min_node(game, hash, mov, depth, ply, alpha, beta, stop)?;
+            if value > best.0 {
+                best = (value, mov);
+            }
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best.0 <= original_alpha {
+            Bound::Upper
+        } else if best.0 >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.insert(
+            hash,
+            Entry {
+                check,
+                depth,
+                value: to_table(best.0, ply),
+                bound,
+                best_move: best.1,
+            },
+        );
+
+        Some(best.0)
+    }
+
+    /// The other snakes answer `our_move`, played from the position of `hash`.
+    #[allow(clippy::too_many_arguments)]
+    fn min_node(
+        &mut self,
+        game: &mut SnakeGame,
+        hash: u64,
+        our_move: Movement,
+        depth: u32,
+        ply: u32,
+        alpha: i64,
+        mut beta: i64,
+        stop: &AtomicBool,
+    ) -> Option<i64> {
+        let mut replies: Vec<Vec<Movement>> = (0..game.board().snakes().len())
+            .map(|id| {
+                if id == self.id {
+                    vec![our_move]
+                } else {
+                    game.searched_moves(id)
+                }
+            })
+            .multi_cartesian_product()
+            .collect();
+
+        // the answer that was the worst for us before first
+        if let Some(refutation) = self.refutations.get(&(hash, our_move)) {
+            if let Some(i) = replies.iter().position(|r| r == refutation) {
+                replies.swap(0, i);
+            }
+        }
+
+        if self.undos.len() <= ply as usize {
+            self.undos.resize_with(ply as usize + 1, Undo::default);
+        }
+        let mut undo = mem::take(&mut self.undos[ply as usize]);
+
+        let mut worst = (i64::MAX, None);
+        for reply in replies {
+            game.play(&reply, &mut undo);
+            let value = self.max_node(game, depth - 1, ply + 1, alpha, beta, stop);
+            game.take_back(&undo);
+
+            let value = match value {
+                Some(value) => value,
+                None => {
+                    self.undos[ply as usize] = undo;
+                    return None;
+                }
+            };
+            if value < worst.0 {
+                worst = (value, Some(reply));
+            }
+            beta = beta.min(value);
+            if alpha >= beta {
+                break;
+            }
+        }
+        self.undos[ply as usize] = undo;
+
+        if let (_, Some(refutation)) = worst {
+            self.refutations.insert((hash, our_move), refutation);
+        }
+
+        Some(worst.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::fixtures::dead_end;
+    use crate::engine::{Board, Point, Snake};
+    use std::sync::atomic::AtomicBool;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn paranoid_avoids_dead_ends() {
+        let game = dead_end();
+        let stop = AtomicBool::new(false);
+        let mut paranoid = Paranoid::new(0);
+        let best_move = thread::scope(|s| {
+            s.spawn(|| {
+                thread::sleep(Duration::from_millis(200));
+                stop.store(true, Ordering::Relaxed);
+            });
+            paranoid.iterative_deepening(&game, &stop)
+        });
+
+        assert_eq!(best_move, Some(Movement::Right));
+    }
+
+    #[test]
+    fn searches_keep_their_best_moves_and_refutations() {
+        let mut game = dead_end();
+        game.set_player(0);
+        game.set_food_sampling(false);
+        let (stop, mut paranoid) = (AtomicBool::new(false), Paranoid::new(0));
+        assert!(paranoid
+            .max_node(&mut game, 3, 0, -WIN, WIN, &stop)
+            .is_some());
+
+        let hash = TranspositionHash::hash(&game);
+        assert_eq!(paranoid.entry(&game).unwrap().best_move, Movement::Right);
+        // the worst answer to each of our moves, which holds our move too
+        for mov in game.available_moves_snake(0) {
+            assert_eq!(paranoid.refutations[&(hash, mov)][0], mov);
+        }
+
+        // a position searched deep enough is answered by the table
+        let entry = paranoid.table.get_mut(&hash).unwrap();
+        entry.value = 1234;
+        entry.bound = Bound::Exact;
+        assert_eq!(
+            paranoid.max_node(&mut game, 3, 0, -WIN, WIN, &stop),
+            Some(1234)
+        );
+        assert_eq!(
+            paranoid.max_node(&mut game, 2, 0, -WIN, WIN, &stop),
+            Some(1234)
+        );
+
+        // a deeper search doesn't trust it
+        assert_ne!(
+            paranoid.max_node(&mut game, 4, 0, -WIN, WIN, &stop),
+            Some(1234)
+        );
+    }
+
+    #[test]
+    fn table_tells_positions_apart_and_keeps_mates_relative() {
+        let game = |health| {
+            let snakes = vec![
+                Snake::new_from(health, vec![Point { x: 0, y: 0 }], 1, Point { x: 0, y: 0 }),
+                Snake::new_from(50, vec![Point { x: 4, y: 4 }], 1, Point { x: 4, y: 4 }),
+            ];
+            SnakeGame::new(Board::new_from(5, 5, snakes, &[], &[]))
+        };
+
+        // same tens of health, same hash, yet another position
+        let (a, b) = (game(41), game(48));
+        assert_eq!(TranspositionHash::hash(&a), TranspositionHash::hash(&b));
+        assert_ne!(verification_key(&a), verification_key(&b));
+
+        // a win in 3 turns from a position 2 turns deep is a win in 5 turns from the root
+        assert_eq!(to_table(WIN - 5, 2), WIN - 3);
+        assert_eq!(from_table(WIN - 3, 2), WIN - 5);
+        assert_eq!(from_table(to_table(-WIN + 7, 4), 1), -WIN + 4);
+        assert_eq!(to_table(DRAW, 4), DRAW);
+    }
+}
@@ -0,0 +1,86 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::paranoid::Paranoid;
+use super::player::BackgroundSearch;
+use super::Player;
+use crate::engine::{safe_move, Movement, SnakeGame};
+
+/// Sleep of the watcher raising the stop flag of `search_until`, the search only reads the flag
+/// between two nodes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Searches with `Paranoid` in a thread of its own, keeping its tables from a turn to the next.
+pub struct ParanoidBot {
+    id: usize,
+    color: [f32; 4],
+    search: Option<BackgroundSearch<Paranoid>>,
+    /// Tables of the last search, still useful a turn later.
+    paranoid: Option<Paranoid>,
+}
+
+impl ParanoidBot {
+    pub fn new(id: usize, color: [f32; 4]) -> Self {
+        ParanoidBot {
+            id,
+            color,
+            search: None,
+            paranoid: None,
+        }
+    }
+}
+
+impl Player for ParanoidBot {
+    fn think(&mut self, game: &SnakeGame) {
+        let id = self.id;
+        let mut paranoid = self.paranoid.take().unwrap_or_else(|| Paranoid::new(id));
+        let searched = game.clone();
+        let search = BackgroundSearch::start(game, id, move |stop| {
+            let best_move = paranoid.iterative_deepening(&searched, stop);
+            (paranoid, best_move)
+        });
+        self.search = Some(search);
+    }
+
+    fn next_move(&mut self) -> Movement {
+        // tables lost to a panic start empty at the next turn
+        let (paranoid, best_move) = match self.search.take() {
+            Some(search) => search.stop(),
+            None => return Movement::Up,
+        };
+        self.paranoid = paranoid;
+        best_move
+    }
+
+    fn get_color(&self) -> [f32; 4] {
+        self.color
+    }
+
+    /// Searches in the calling thread, until the deadline or a search seeing the end of the
+    /// game.
+    fn search_until(&mut self, game: &SnakeGame, deadline: Instant) -> Movement {
+        let id = self.id;
+        let mut paranoid = self.paranoid.take().unwrap_or_else(|| Paranoid::new(id));
+        let (stop, done) = (AtomicBool::new(false), AtomicBool::new(false));
+
+        let best_move = thread::scope(|s| {
+            s.spawn(|| {
+                while !done.load(Ordering::Relaxed) {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        stop.store(true, Ordering::Relaxed);
+                        break;
+                    }
+                    thread::sleep(WATCH_INTERVAL.min(deadline - now));
+                }
+            });
+            let best_move = paranoid.iterative_deepening(game, &stop);
+            done.store(true, Ordering::Relaxed);
+            best_move
+        });
+
+        self.paranoid = Some(paranoid);
+        best_move.unwrap_or_else(|| safe_move(game, id))
+    }
+}
@@ -1,9 +1,81 @@
+#[cfg(feature = "search")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "search")]
+use std::sync::Arc;
+#[cfg(feature = "search")]
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+#[cfg(feature = "search")]
+use crate::engine::safe_move;
 use crate::engine::{Movement, SnakeGame};
-use piston_window::Button;
 
-pub trait Player {
+pub trait Player: Send {
     fn think(&mut self, game: &SnakeGame);
     fn next_move(&mut self) -> Movement;
     fn get_color(&self) -> [f32; 4];
-    fn register_key_event(&mut self, press_args: Button);
+
+    /// Move in `game`, searched until `deadline` at the latest. The bots searching between
+    /// `think` and `next_move` stop at the deadline, the others answer once they thought.
+    fn search_until(&mut self, game: &SnakeGame, _deadline: Instant) -> Movement {
+        self.think(game);
+        self.next_move()
+    }
+}
+
+/// Search of a bot running in a thread of its own from `think` to `next_move`. The search
+/// looks at a flag to know when to stop, and hands back what the bot keeps for the next turn
+/// along with the move it found.
+#[cfg(feature = "search")]
+pub(crate) struct BackgroundSearch<T> {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<(T, Option<Movement>)>,
+    /// Safe move of the searched position, for a search finding no move or panicking.
+    fallback: Movement,
+}
+
+#[cfg(feature = "search")]
+impl<T: Send + 'static> BackgroundSearch<T> {
+    /// Starts `search` for the snake `id` of `game`.
+    pub(crate) fn start<F>(game: &SnakeGame, id: usize, search: F) -> Self
+    where
+        F: FnOnce(&AtomicBool) -> (T, Option<Movement>) + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let stop = stop.clone();
+            thread::spawn(move || search(&stop))
+        };
+        BackgroundSearch {
+            stop,
+            handle,
+            fallback: safe_move(game, id),
+        }
+    }
+
+    /// Stops the search and waits for its move. What the search kept is lost when it panicked.
+    pub(crate) fn stop(self) -> (Option<T>, Movement) {
+        self.stop.store(true, Ordering::Relaxed);
+        match self.handle.join() {
+            Ok((kept, best_move)) => (Some(kept), best_move.unwrap_or(self.fallback)),
+            Err(_) => (None, self.fallback),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "search"))]
+mod test {
+    use super::*;
+    use crate::engine::fixtures::dead_end;
+
+    #[test]
+    fn failed_searches_answer_the_safe_move() {
+        let search = BackgroundSearch::start(&dead_end(), 0, |_| ((), None));
+        assert_eq!(search.stop(), (Some(()), Movement::Right));
+
+        let search = BackgroundSearch::start(&dead_end(), 0, |_| -> ((), _) {
+            panic!("the search failed")
+        });
+        assert_eq!(search.stop(), (None, Movement::Right));
+    }
 }
@@ -1,8 +1,8 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::engine::Movement;
 
-#[derive(Deserialize, PartialEq, Eq, Debug, Copy, Clone, Hash)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Copy, Clone, Hash)]
 pub struct Point {
     pub x: i32,
     pub y: i32,
@@ -29,4 +29,63 @@ impl Point {
             },
         }
     }
+
+    /// Brings back a point that left the board on the opposite side.
+    pub fn wrap(&self, width: i32, height: i32) -> Point {
+        Point {
+            x: self.x.rem_euclid(width),
+            y: self.y.rem_euclid(height),
+        }
+    }
+}
+
+/// Shape of the board: its dimensions and what happens at its edges.
+#[derive(Hash, PartialEq, Eq, Debug, Copy, Clone)]
+pub struct Geometry {
+    pub width: i32,
+    pub height: i32,
+    pub wrapped: bool,
+}
+
+impl Geometry {
+    pub fn new(width: i32, height: i32, wrapped: bool) -> Self {
+        Geometry {
+            width,
+            height,
+            wrapped,
+        }
+    }
+
+    /// Moves `p`, wrapping around the edges on wrapped boards.
+    #[inline]
+    pub fn apply_mov(&self, p: Point, mov: Movement) -> Point {
+        let p = p.apply_mov(mov);
+        if self.wrapped {
+            p.wrap(self.width, self.height)
+        } else {
+            p
+        }
+    }
+
+    #[inline]
+    pub fn is_outside(&self, p: Point) -> bool {
+        p.x < 0 || p.x >= self.width || p.y < 0 || p.y >= self.height
+    }
+
+    /// Number of moves from `a` to `b` on an empty board.
+    #[inline]
+    pub fn distance(&self, a: Point, b: Point) -> i32 {
+        let (dx, dy) = ((a.x - b.x).abs(), (a.y - b.y).abs());
+        if self.wrapped {
+            dx.min(self.width - dx) + dy.min(self.height - dy)
+        } else {
+            dx + dy
+        }
+    }
+
+    /// Cell reached from `p` with `mov`, `None` when it falls off the board.
+    #[inline]
+    pub fn neighbour(&self, p: Point, mov: Movement) -> Option<Point> {
+        Some(self.apply_mov(p, mov)).filter(|&p| !self.is_outside(p))
+    }
 }
@@ -0,0 +1,38 @@
+use rand::seq::SliceRandom;
+
+use super::Player;
+use crate::engine::{Movement, SnakeGame};
+
+/// Plays a random move among the ones that don't kill it right away, a baseline for the arena.
+pub struct RandomBot {
+    id: usize,
+    color: [f32; 4],
+    next_move: Option<Movement>,
+}
+
+impl RandomBot {
+    pub fn new(id: usize, color: [f32; 4]) -> Self {
+        RandomBot {
+            id,
+            color,
+            next_move: None,
+        }
+    }
+}
+
+impl Player for RandomBot {
+    fn think(&mut self, game: &SnakeGame) {
+        self.next_move = game
+            .available_moves_snake(self.id)
+            .choose(&mut rand::thread_rng())
+            .copied();
+    }
+
+    fn next_move(&mut self) -> Movement {
+        self.next_move.take().unwrap_or(Movement::Up)
+    }
+
+    fn get_color(&self) -> [f32; 4] {
+        self.color
+    }
+}
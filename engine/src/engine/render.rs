@@ -1,3 +1,59 @@
-extern crate piston_window;
+use super::{Board, Point};
 
-use piston_window::*;
+/// What a renderer draws on a cell of the board, hazards are drawn under it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    Empty,
+    Food,
+    Head { id: usize },
+    Body { id: usize },
+}
+
+impl Board {
+    /// Heads are drawn above bodies so that head to body collisions stay visible.
+    pub fn tile(&self, p: Point) -> Tile {
+        if let Some((id, _)) = self.alive_snakes().find(|(_, s)| *s.head() == p) {
+            return Tile::Head { id };
+        }
+
+        if let Some((id, _)) = self.alive_snakes().find(|(_, s)| s.body().contains(&p)) {
+            return Tile::Body { id };
+        }
+
+        if self.food().contains(&p) {
+            Tile::Food
+        } else {
+            Tile::Empty
+        }
+    }
+
+    /// Cells of the board row by row, from `y = 0`.
+    pub fn tiles(&self) -> impl Iterator<Item = (Point, Tile)> + '_ {
+        (0..self.height())
+            .flat_map(move |y| (0..self.width()).map(move |x| Point { x, y }))
+            .map(move |p| (p, self.tile(p)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::{Movement, Snake};
+
+    #[test]
+    fn tiles_of_a_board() {
+        let snakes = vec![
+            Snake::new(Point { x: 1, y: 1 }),
+            Snake::new(Point { x: 3, y: 3 }),
+        ];
+        let mut board = Board::new_from(5, 5, snakes, &[Point { x: 0, y: 4 }], &[]);
+        board.step(vec![Movement::Right, Movement::Left], true);
+
+        assert_eq!(board.tile(Point { x: 2, y: 1 }), Tile::Head { id: 0 });
+        assert_eq!(board.tile(Point { x: 1, y: 1 }), Tile::Body { id: 0 });
+        assert_eq!(board.tile(Point { x: 2, y: 3 }), Tile::Head { id: 1 });
+        assert_eq!(board.tile(Point { x: 0, y: 4 }), Tile::Food);
+        assert_eq!(board.tile(Point { x: 4, y: 4 }), Tile::Empty);
+        assert_eq!(board.tiles().count(), 25);
+    }
+}
@@ -0,0 +1,195 @@
+//! JSON-lines replays of local games.
+//!
+//! Every line is a `Frame`: the first one holds the initial board with no moves, then there is
+//! one line per turn with the moves played, what happened during the step and the resulting
+//! board. Boards use the `BoardState` format. Frames also hold the settings of the game, the
+//! ones of the last frame replayed are given to the rebuilt game.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Board, Collision, Movement, Point, SnakeGame};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Frame {
+    /// Moves of every snake, indexed by snake id, that led to `board`.
+    pub moves: Vec<Movement>,
+    pub collisions: Vec<Collision>,
+    pub spawned_food: Vec<Point>,
+    pub board: Board,
+    /// See `SnakeGame::current_player`.
+    #[serde(default)]
+    pub player: usize,
+    #[serde(default)]
+    pub food_sampling: bool,
+    #[serde(default)]
+    pub pruning_distance: Option<i32>,
+}
+
+impl Frame {
+    fn new(game: &SnakeGame, moves: Vec<Movement>) -> Self {
+        let board = game.board();
+        Frame {
+            moves,
+            collisions: board.collisions().clone(),
+            spawned_food: board.spawned_food().to_vec(),
+            board: board.clone(),
+            player: game.current_player(),
+            food_sampling: game.food_sampling(),
+            pruning_distance: game.pruning_distance(),
+        }
+    }
+}
+
+/// Plays the steps of a `SnakeGame` while writing them to a replay.
+pub struct Recorder<W: Write> {
+    writer: W,
+}
+
+impl Recorder<BufWriter<File>> {
+    pub fn create<P: AsRef<Path>>(path: P, game: &SnakeGame) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), game)
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    /// Writes the initial state of `game`, which must not have been stepped yet for its food
+    /// spawns to be replayable.
+    pub fn new(writer: W, game: &SnakeGame) -> io::Result<Self> {
+        let mut recorder = Recorder { writer };
+        recorder.write(&Frame::new(game, vec![]))?;
+
+        Ok(recorder)
+    }
+
+    pub fn step(&mut self, game: &mut SnakeGame, moves: Vec<Movement>) -> io::Result<()> {
+        game.step(moves.clone());
+
+        let mut frame = Frame::new(game, moves);
+        frame.spawned_food.sort_unstable_by_key(|p| (p.x, p.y));
+        self.write(&frame)
+    }
+
+    fn write(&mut self, frame: &Frame) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, frame)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Replay {
+    frames: Vec<Frame>,
+}
+
+impl Replay {
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    pub fn read<R: BufRead>(reader: R) -> io::Result<Self> {
+        let frames = reader
+            .lines()
+            .filter(|line| line.as_ref().map_or(true, |l| !l.trim().is_empty()))
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect::<io::Result<Vec<Frame>>>()?;
+
+        if frames.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "empty replay"));
+        }
+
+        Ok(Replay { frames })
+    }
+
+    #[inline]
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Number of turns played, the replay holds one more frame for the initial board.
+    #[inline]
+    pub fn nb_turns(&self) -> usize {
+        self.frames.len() - 1
+    }
+
+    /// Replays the recorded moves up to the `turn`-th one. Boards are seeded so the food
+    /// spawns match the recorded game, unlike the board of the frame whose generator restarts.
+    pub fn game_at(&self, turn: usize) -> Option<SnakeGame> {
+        if turn > self.nb_turns() {
+            return None;
+        }
+
+        let mut game = SnakeGame::new(self.frames[0].board.clone());
+        self.frames[1..=turn]
+            .iter()
+            .for_each(|f| game.step(f.moves.clone()));
+
+        let frame = &self.frames[turn];
+        game.set_player(frame.player);
+        game.set_food_sampling(frame.food_sampling);
+        game.set_pruning_distance(frame.pruning_distance);
+
+        Some(game)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::{BoardState, Snake};
+
+    #[test]
+    fn replayed_game_matches_recording() {
+        let snakes = vec![
+            Snake::new(Point { x: 1, y: 1 }),
+            Snake::new(Point { x: 5, y: 5 }),
+        ];
+        let mut game = SnakeGame::new(Board::new(7, 7, snakes).with_seed(11));
+        game.set_player(1);
+        game.set_food_sampling(true);
+
+        let mut buffer = Vec::new();
+        let mut recorder = Recorder::new(&mut buffer, &game).unwrap();
+        for moves in [
+            [Movement::Up, Movement::Down],
+            [Movement::Right, Movement::Left],
+            [Movement::Up, Movement::Down],
+        ] {
+            recorder.step(&mut game, moves.to_vec()).unwrap();
+        }
+
+        let replay = Replay::read(buffer.as_slice()).unwrap();
+        assert_eq!(replay.nb_turns(), 3);
+        let replayed = replay.game_at(3).unwrap();
+        assert_eq!(
+            BoardState::from(replayed.board()),
+            BoardState::from(game.board())
+        );
+        assert_eq!(replayed.current_player(), 1);
+        assert!(replayed.food_sampling());
+        assert!(replay.game_at(4).is_none());
+    }
+
+    #[test]
+    fn spawned_food_is_recorded() {
+        let snakes = vec![Snake::new(Point { x: 1, y: 1 }), Snake::new(Point { x: 5, y: 5 })];
+        let food = [Point { x: 1, y: 2 }];
+        let mut game = SnakeGame::new(Board::new_from(7, 7, snakes, &food, &[]).with_seed(3));
+
+        let mut buffer = Vec::new();
+        let mut recorder = Recorder::new(&mut buffer, &game).unwrap();
+        recorder
+            .step(&mut game, vec![Movement::Up, Movement::Down])
+            .unwrap();
+
+        // the only food got eaten, another one spawns right away
+        let replay = Replay::read(buffer.as_slice()).unwrap();
+        let spawned = &replay.frames()[1].spawned_food;
+        assert_eq!(spawned.len(), 1);
+        assert_eq!(spawned, &game.board().food().iter().copied().collect::<Vec<_>>());
+        assert_eq!(spawned, game.board().spawned_food());
+    }
+}
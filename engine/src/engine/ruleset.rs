@@ -0,0 +1,276 @@
+use std::fmt::Debug;
+
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use super::{board::DEFAULT_HAZARD_DAMAGE, Board, Point};
+
+/// Royale games grow the hazard zone by one row or column every this many turns.
+pub const DEFAULT_SHRINK_EVERY_N_TURNS: u32 = 25;
+
+/// Rules parameters of a game, `game.ruleset.settings` in the arena requests.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+    /// Probability for a food to spawn at the end of a turn, in `[0, 1]`.
+    pub food_spawn_chance: f32,
+    /// Food is spawned right away when there are fewer on the board.
+    pub minimum_food: usize,
+    pub hazard_damage_per_turn: i32,
+    pub shrink_every_n_turns: u32,
+}
+
+impl Default for Settings {
+    /// Settings of the official standard games.
+    fn default() -> Self {
+        Settings {
+            food_spawn_chance: 0.15,
+            minimum_food: 1,
+            hazard_damage_per_turn: DEFAULT_HAZARD_DAMAGE,
+            shrink_every_n_turns: DEFAULT_SHRINK_EVERY_N_TURNS,
+        }
+    }
+}
+
+/// Game mode specific parts of `Board::step`.
+///
+/// Every hook has the standard behaviour by default, game modes only override what differs.
+pub trait Ruleset: Debug + Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Snakes leaving the board come back on the opposite side instead of hitting a wall.
+    fn wraps_edges(&self) -> bool {
+        false
+    }
+
+    /// Pushes the cells where food got eaten to `eaten`.
+    fn feed_snakes(&self, board: &mut Board, eaten: &mut Vec<Point>) {
+        board.feed_snakes(eaten)
+    }
+
+    /// Draws the spawns from `rng`, see `Board::spawn_food`.
+    fn spawn_food(&self, board: &mut Board, rng: &mut dyn RngCore) {
+        board.spawn_food_by_chance(rng);
+    }
+
+    fn update_hazards(&self, _board: &mut Board) {}
+
+    /// Multiplayer games end with the last snake standing, single player ones with its death.
+    fn is_game_over(&self, board: &Board) -> bool {
+        let nb_snakes_alive = board.nb_snakes_alive();
+        if board.snakes().len() == 1 {
+            nb_snakes_alive == 0
+        } else {
+            nb_snakes_alive <= 1
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Standard;
+
+impl Ruleset for Standard {
+    fn name(&self) -> &'static str {
+        "standard"
+    }
+}
+
+#[derive(Debug)]
+pub struct Solo;
+
+impl Ruleset for Solo {
+    fn name(&self) -> &'static str {
+        "solo"
+    }
+
+    fn is_game_over(&self, board: &Board) -> bool {
+        board.nb_snakes_alive() == 0
+    }
+}
+
+#[derive(Debug)]
+pub struct Royale;
+
+impl Ruleset for Royale {
+    fn name(&self) -> &'static str {
+        "royale"
+    }
+
+    fn update_hazards(&self, board: &mut Board) {
+        let (turn, every_n_turns) = (board.turn(), board.settings().shrink_every_n_turns);
+        if turn > 0 && every_n_turns > 0 && turn % every_n_turns == 0 {
+            let side = board.rng().gen();
+            board.shrink_safe_zone(side);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Constrictor;
+
+impl Ruleset for Constrictor {
+    fn name(&self) -> &'static str {
+        "constrictor"
+    }
+
+    // snakes never starve and grow at every turn
+    fn feed_snakes(&self, board: &mut Board, _eaten: &mut Vec<Point>) {
+        board.alive_snakes_mut().for_each(|(_, s)| s.feed());
+    }
+
+    fn spawn_food(&self, _board: &mut Board, _rng: &mut dyn RngCore) {}
+}
+
+#[derive(Debug)]
+pub struct Wrapped;
+
+impl Ruleset for Wrapped {
+    fn name(&self) -> &'static str {
+        "wrapped"
+    }
+
+    fn wraps_edges(&self) -> bool {
+        true
+    }
+}
+
+/// Unknown game modes are played with the standard rules.
+pub fn ruleset_from_name(name: &str) -> &'static dyn Ruleset {
+    match name {
+        "solo" => &Solo,
+        "royale" => &Royale,
+        "constrictor" => &Constrictor,
+        "wrapped" => &Wrapped,
+        _ => &Standard,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::{Movement, Outcome, Snake, DEFAULT_SNAKE_HEALTH};
+
+    /// A snake of a single cell, free to turn back.
+    fn snake(x: i32, y: i32, health: i32) -> Snake {
+        Snake::new_from(health, vec![Point { x, y }], 1, Point { x, y })
+    }
+
+    #[test]
+    fn unknown_rulesets_play_standard() {
+        for &name in &["standard", "solo", "royale", "constrictor", "wrapped"] {
+            assert_eq!(ruleset_from_name(name).name(), name);
+        }
+        assert_eq!(ruleset_from_name("squad").name(), "standard");
+        assert_eq!(ruleset_from_name("").name(), "standard");
+    }
+
+    #[test]
+    fn constrictor_snakes_grow_and_never_starve() {
+        let snakes = vec![snake(0, 0, 5), snake(4, 0, 5)];
+        let mut board = Board::new_from(5, 9, snakes, &[], &[])
+            .with_ruleset(ruleset_from_name("constrictor"))
+            .with_seed(1);
+
+        for turn in 1..=8 {
+            board.step(vec![Movement::Up, Movement::Up], false);
+
+            assert_eq!(board.nb_snakes_alive(), 2);
+            for s in board.snakes() {
+                assert_eq!(s.health(), DEFAULT_SNAKE_HEALTH);
+                assert_eq!(s.length(), 1 + turn);
+            }
+            assert!(board.food().is_empty());
+        }
+    }
+
+    #[test]
+    fn royale_hazards_close_in_and_hurt() {
+        let settings = Settings {
+            food_spawn_chance: 0.,
+            minimum_food: 0,
+            shrink_every_n_turns: 2,
+            ..Settings::default()
+        };
+        let center = Point { x: 1, y: 1 };
+        let mut board = Board::new_from(3, 3, vec![snake(1, 1, 50)], &[], &[])
+            .with_ruleset(ruleset_from_name("royale"))
+            .with_settings(settings)
+            .with_seed(3);
+
+        board.step(vec![Movement::Up], false);
+        assert!(board.hazards().is_empty());
+        board.step(vec![Movement::Down], false);
+
+        // a whole border of the board, next to the center
+        assert_eq!(board.hazards().len(), 3);
+        let into_hazard = [
+            Movement::Down,
+            Movement::Left,
+            Movement::Up,
+            Movement::Right,
+        ]
+        .iter()
+        .copied()
+        .find(|&m| board.is_hazard(board.geometry().apply_mov(center, m)))
+        .unwrap();
+
+        board.step(vec![into_hazard], false);
+        assert_eq!(board.hazards().len(), 3);
+        assert_eq!(board.snakes()[0].health(), 50 - 3 - board.hazard_damage());
+    }
+
+    #[test]
+    fn seeded_royale_games_shrink_alike() {
+        let play = || {
+            let settings = Settings {
+                shrink_every_n_turns: 1,
+                ..Settings::default()
+            };
+            let mut board = Board::new_from(7, 7, vec![snake(3, 3, 100)], &[], &[])
+                .with_ruleset(ruleset_from_name("royale"))
+                .with_settings(settings)
+                .with_seed(5);
+            for _ in 0..4 {
+                board.step(vec![Movement::Up], false);
+                board.step(vec![Movement::Down], false);
+            }
+            let mut hazards: Vec<(i32, i32)> = board.hazards().iter().map(|p| (p.x, p.y)).collect();
+            hazards.sort_unstable();
+            hazards
+        };
+
+        assert!(!play().is_empty());
+        assert_eq!(play(), play());
+    }
+
+    #[test]
+    fn solo_games_go_on_with_a_snake_left() {
+        let outcome = |name| {
+            let snakes = vec![snake(0, 0, 50), snake(3, 3, 50)];
+            let mut board =
+                Board::new_from(5, 5, snakes, &[], &[]).with_ruleset(ruleset_from_name(name));
+            board.step(vec![Movement::Left, Movement::Up], true);
+            board.outcome()
+        };
+
+        assert_eq!(outcome("standard"), Outcome::Win { id: 1 });
+        assert_eq!(outcome("solo"), Outcome::Ongoing);
+    }
+
+    #[test]
+    fn wrapped_edges_lead_to_the_other_side() {
+        let play = |name| {
+            let snakes = vec![snake(4, 2, 50), snake(2, 4, 50)];
+            let mut board =
+                Board::new_from(5, 5, snakes, &[], &[]).with_ruleset(ruleset_from_name(name));
+            board.step(vec![Movement::Right, Movement::Up], true);
+            board
+        };
+
+        let board = play("wrapped");
+        assert_eq!(*board.snakes()[0].head(), Point { x: 0, y: 2 });
+        assert_eq!(*board.snakes()[1].head(), Point { x: 2, y: 0 });
+        assert_eq!(board.outcome(), Outcome::Ongoing);
+
+        assert_eq!(play("standard").outcome(), Outcome::Draw);
+    }
+}
@@ -0,0 +1,129 @@
+//! Moves to answer with when the search can't give one.
+//!
+//! From the best to the last resort: a move leading to enough room for the snake and away
+//! from the heads of the snakes that would win a head to head, then any move that doesn't kill
+//! it right away, then any move staying on the board.
+//!
+//! The search guesses the moves of the snakes it doesn't branch on the same way, looking a cell
+//! ahead only: it does so at every turn it plays.
+
+use super::bitboard::BitBoard;
+use super::matrice::Matrice;
+use crate::engine::{Movement, Point, SnakeGame};
+
+const MOVES: [Movement; 4] = [
+    Movement::Down,
+    Movement::Left,
+    Movement::Up,
+    Movement::Right,
+];
+
+/// Number of free cells reachable from `start`, included.
+fn reachable_area(matrice: &Matrice, start: Point) -> usize {
+    let layout = matrice.layout();
+    let free = layout.cells().difference(matrice.occupied());
+
+    let mut cells = BitBoard::default();
+    cells.insert(layout.index(start));
+    layout.flood_fill(cells, &free).len()
+}
+
+/// Cells where a snake at least as long as snake `id` may move.
+fn contested_cells(game: &SnakeGame, id: usize) -> Vec<Point> {
+    let board = game.board();
+    let matrice = board.matrice();
+    let length = board.snakes()[id].length();
+
+    board
+        .snakes()
+        .iter()
+        .enumerate()
+        .filter(|&(i, s)| i != id && !s.is_dead() && s.length() >= length)
+        .flat_map(|(_, s)| {
+            let head = *s.head();
+            MOVES
+                .iter()
+                .filter_map(move |&m| matrice.neighbour(head, m))
+        })
+        .collect()
+}
+
+/// Any move staying on the board.
+fn on_board_move(matrice: &Matrice, head: Point) -> Movement {
+    MOVES
+        .iter()
+        .copied()
+        .find(|&m| matrice.neighbour(head, m).is_some())
+        .unwrap_or(Movement::Up)
+}
+
+/// A move for snake `id` that doesn't need any search, see the module documentation.
+pub fn safe_move(game: &SnakeGame, id: usize) -> Movement {
+    let board = game.board();
+    let matrice = board.matrice();
+    let snake = &board.snakes()[id];
+    let head = *snake.head();
+    let contested = contested_cells(game, id);
+
+    let best = game
+        .non_fatal_moves(id)
+        .into_iter()
+        .filter_map(|m| Some((m, matrice.neighbour(head, m)?)))
+        .map(|(m, p)| {
+            let area = reachable_area(matrice, p);
+            let safe = area >= snake.length() && !contested.contains(&p);
+            (m, safe, area)
+        })
+        .max_by_key(|&(_, safe, area)| (safe, area))
+        .map(|(m, _, _)| m);
+
+    best.unwrap_or_else(|| on_board_move(matrice, head))
+}
+
+/// Guessed move of snake `id`, like `safe_move` but counting the free cells next to each move
+/// instead of the room behind it.
+pub(crate) fn predicted_move(game: &SnakeGame, id: usize) -> Movement {
+    let matrice = game.board().matrice();
+    let head = *game.board().snakes()[id].head();
+    let contested = contested_cells(game, id);
+    let free_neighbours = |p: Point| {
+        MOVES
+            .iter()
+            .filter_map(|&m| matrice.neighbour(p, m))
+            .filter(|&n| matrice.get(n).is_none())
+            .count()
+    };
+
+    game.non_fatal_moves(id)
+        .into_iter()
+        .filter_map(|m| Some((m, matrice.neighbour(head, m)?)))
+        .max_by_key(|&(_, p)| (!contested.contains(&p), free_neighbours(p)))
+        .map_or_else(|| on_board_move(matrice, head), |(m, _)| m)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::fixtures::{dead_end, snake};
+    use crate::engine::Board;
+
+    #[test]
+    fn safe_move_avoids_dead_ends() {
+        let game = dead_end();
+        assert_eq!(game.non_fatal_moves(0).len(), 2);
+        assert_eq!(safe_move(&game, 0), Movement::Right);
+        assert_eq!(predicted_move(&game, 0), Movement::Right);
+
+        // nothing is safe, the move still stays on the board
+        let snakes = vec![
+            snake(&[(0, 2), (0, 1), (0, 0)]),
+            snake(&[(2, 1), (1, 1), (1, 0)]),
+        ];
+        let game = SnakeGame::new(Board::new_from(5, 5, snakes, &[], &[]));
+        assert!(game.non_fatal_moves(0).is_empty());
+        assert!(matches!(
+            safe_move(&game, 0),
+            Movement::Up | Movement::Right
+        ));
+    }
+}
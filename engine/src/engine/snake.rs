@@ -1,12 +1,12 @@
 use std::{collections::VecDeque, iter::FromIterator};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use super::{Movement, Point};
+use super::Point;
 
 pub const DEFAULT_SNAKE_HEALTH: i32 = 100;
 
-#[derive(Deserialize, PartialEq, Eq, Debug, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
 pub struct Snake {
     health: i32,
     body: VecDeque<Point>,
@@ -33,7 +33,7 @@ impl Snake {
         }
     }
 
-    pub fn apply_move(&mut self, mov: Movement) -> (Option<Point>, Point) {
+    pub fn move_head_to(&mut self, head: Point) -> (Option<Point>, Point) {
         debug_assert!(!self.is_dead());
 
         // Last body part (their tail) is removed from the board
@@ -45,7 +45,7 @@ impl Snake {
         };
 
         // A new body part is added to the board in the direction they moved.
-        self.head = self.head.apply_mov(mov);
+        self.head = head;
         self.body.push_back(self.head);
 
         // moves cost one life point
@@ -60,6 +60,12 @@ impl Snake {
         self.health -= 1;
     }
 
+    pub fn damage(&mut self, amount: i32) {
+        debug_assert!(!self.is_dead());
+
+        self.health = (self.health - amount).max(0);
+    }
+
     pub fn kill(&mut self) {
         debug_assert!(!self.is_dead());
 
@@ -77,6 +83,30 @@ impl Snake {
         self.length += 1;
     }
 
+    /// Takes back `move_head_to`, `tail` being the part it removed.
+    pub(crate) fn undo_move(
+        &mut self,
+        head: Point,
+        tail: Option<Point>,
+        health: i32,
+        length: usize,
+    ) {
+        self.body.pop_back();
+        if let Some(tail) = tail {
+            self.body.push_front(tail);
+        }
+        self.head = head;
+        self.health = health;
+        self.length = length;
+    }
+
+    /// Takes back `kill`, the length coming back with `undo_move`.
+    pub(crate) fn revive(&mut self, body: &[Point]) {
+        debug_assert!(self.body.is_empty());
+
+        self.body.extend(body);
+    }
+
     #[inline]
     pub fn is_dead(&self) -> bool {
         self.length == 0
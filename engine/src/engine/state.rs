@@ -0,0 +1,141 @@
+//! Stable JSON representation of a `Board`.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "width": 11,
+//!   "height": 11,
+//!   "turn": 42,
+//!   "ruleset": "standard",
+//!   "settings": {
+//!     "food_spawn_chance": 0.15,
+//!     "minimum_food": 1,
+//!     "hazard_damage_per_turn": 14,
+//!     "shrink_every_n_turns": 25
+//!   },
+//!   "seed": 1234,
+//!   "food": [{"x": 5, "y": 5}],
+//!   "hazards": [],
+//!   "snakes": [
+//!     {"health": 98, "body": [{"x": 1, "y": 1}, {"x": 1, "y": 2}], "head": {"x": 1, "y": 2}, "length": 3}
+//!   ]
+//! }
+//! ```
+//!
+//! Snakes are listed by id, dead ones included with an empty body, and their body goes from
+//! the tail to the head. Food and hazards are sorted so that equal boards give equal
+//! documents. The random generator isn't saved, a deserialized board restarts it from `seed`.
+//!
+//! `version` is bumped on every breaking change of the format, older documents are rejected.
+
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+
+use super::bitboard::check_size;
+use super::{ruleset_from_name, Board, Point, Settings, Snake};
+
+pub const STATE_FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct BoardState {
+    pub version: u32,
+    pub width: i32,
+    pub height: i32,
+    pub turn: u32,
+    pub ruleset: String,
+    pub settings: Settings,
+    pub seed: u64,
+    pub food: Vec<Point>,
+    pub hazards: Vec<Point>,
+    pub snakes: Vec<Snake>,
+}
+
+fn sorted_points<'a>(points: impl Iterator<Item = &'a Point>) -> Vec<Point> {
+    let mut points: Vec<Point> = points.copied().collect();
+    points.sort_unstable_by_key(|p| (p.x, p.y));
+    points
+}
+
+impl From<&Board> for BoardState {
+    fn from(board: &Board) -> Self {
+        BoardState {
+            version: STATE_FORMAT_VERSION,
+            width: board.width(),
+            height: board.height(),
+            turn: board.turn(),
+            ruleset: board.ruleset().name().to_string(),
+            settings: *board.settings(),
+            seed: board.seed(),
+            food: sorted_points(board.food().iter()),
+            hazards: sorted_points(board.hazards().iter()),
+            snakes: board.snakes().clone(),
+        }
+    }
+}
+
+impl From<Board> for BoardState {
+    fn from(board: Board) -> Self {
+        BoardState::from(&board)
+    }
+}
+
+impl TryFrom<BoardState> for Board {
+    type Error = String;
+
+    fn try_from(state: BoardState) -> Result<Self, Self::Error> {
+        if state.version != STATE_FORMAT_VERSION {
+            return Err(format!(
+                "unsupported board format version {} (expected {})",
+                state.version, STATE_FORMAT_VERSION
+            ));
+        }
+
+        let ruleset = ruleset_from_name(&state.ruleset);
+        if ruleset.name() != state.ruleset {
+            return Err(format!("unknown ruleset {}", state.ruleset));
+        }
+        check_size(state.width, state.height)?;
+
+        Ok(Board::new_from(
+            state.width,
+            state.height,
+            state.snakes,
+            &state.food,
+            &state.hazards,
+        )
+        .with_ruleset(ruleset)
+        .with_settings(state.settings)
+        .with_turn(state.turn)
+        .with_seed(state.seed))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn board_json_round_trip() {
+        let snakes = vec![Snake::new(Point { x: 1, y: 1 }), Snake::new(Point { x: 5, y: 5 })];
+        let board = Board::new_from(7, 7, snakes, &[Point { x: 3, y: 3 }], &[Point { x: 0, y: 6 }])
+            .with_ruleset(ruleset_from_name("constrictor"))
+            .with_turn(12)
+            .with_seed(3);
+
+        let json = serde_json::to_string(&board).unwrap();
+        let copy: Board = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(BoardState::from(&copy), BoardState::from(&board));
+        assert_eq!(copy.matrice(), board.matrice());
+    }
+
+    #[test]
+    fn reject_other_versions() {
+        let board = Board::new(7, 7, vec![Snake::new(Point { x: 1, y: 1 })]);
+        let mut state = BoardState::from(&board);
+        state.version += 1;
+
+        assert!(Board::try_from(state).is_err());
+    }
+}
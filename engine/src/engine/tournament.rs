@@ -0,0 +1,425 @@
+//! Tournaments between bot configurations, rated with Elo.
+//!
+//! Every game updates the ratings of both bots, so that a leaderboard keeps improving over
+//! several tournaments. The confidence interval of a rating comes from the spread of the
+//! results of the bot, like in engine testing tools.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Arena, BotConfig, Outcome};
+
+pub const INITIAL_RATING: f64 = 1500.;
+const K_FACTOR: f64 = 16.;
+/// Quantile of the normal distribution for a 95% confidence interval.
+const Z_95: f64 = 1.96;
+
+/// Expected score of a player rated `a` against one rated `b`, in `[0, 1]`.
+pub fn expected_score(a: f64, b: f64) -> f64 {
+    1. / (1. + 10f64.powf((b - a) / 400.))
+}
+
+/// Rating difference at which `score` is expected, the inverse of `expected_score`.
+fn rating_difference(score: f64) -> f64 {
+    -400. * (1. / score - 1.).log10()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// Written form of the `BotConfig`.
+    pub bot: String,
+    pub rating: f64,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl Entry {
+    fn new(bot: &str) -> Self {
+        Entry {
+            bot: bot.to_string(),
+            rating: INITIAL_RATING,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+        }
+    }
+
+    #[inline]
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Average score per game, draws count for half a win.
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.) / self.games() as f64
+    }
+
+    /// Half width of the 95% confidence interval of the rating, `None` while the bot has only
+    /// won or only lost, which gives an unbounded interval.
+    pub fn error_margin(&self) -> Option<f64> {
+        let (n, score) = (self.games() as f64, self.score());
+        let variance = (self.wins as f64 * (1. - score).powi(2)
+            + self.draws as f64 * (0.5 - score).powi(2)
+            + self.losses as f64 * score.powi(2))
+            / n;
+        let deviation = Z_95 * (variance / n).sqrt();
+
+        let (low, high) = (score - deviation, score + deviation);
+        if low <= 0. || high >= 1. {
+            return None;
+        }
+
+        Some((rating_difference(high) - rating_difference(low)) / 2.)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Leaderboard {
+    entries: Vec<Entry>,
+}
+
+impl Leaderboard {
+    /// A missing file gives an empty leaderboard.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        match File::open(path) {
+            Ok(file) => Ok(serde_json::from_reader(BufReader::new(file))?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Leaderboard::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        serde_json::to_writer_pretty(BufWriter::new(File::create(path)?), self)?;
+        Ok(())
+    }
+
+    pub fn get(&self, bot: &str) -> Option<&Entry> {
+        self.entries.iter().find(|e| e.bot == bot)
+    }
+
+    /// New bots start at `INITIAL_RATING`.
+    pub fn rating(&self, bot: &str) -> f64 {
+        self.get(bot).map_or(INITIAL_RATING, |e| e.rating)
+    }
+
+    fn entry_mut(&mut self, bot: &str) -> &mut Entry {
+        match self.entries.iter().position(|e| e.bot == bot) {
+            Some(i) => &mut self.entries[i],
+            None => {
+                self.entries.push(Entry::new(bot));
+                self.entries.last_mut().unwrap()
+            }
+        }
+    }
+
+    /// Records a game between `a` and `b`, `score` is the one of `a`: 1 for a win, 0.5 for a
+    /// draw and 0 for a loss.
+    pub fn record(&mut self, a: &str, b: &str, score: f64) {
+        let delta = K_FACTOR * (score - expected_score(self.rating(a), self.rating(b)));
+
+        for (bot, score, delta) in [(a, score, delta), (b, 1. - score, -delta)] {
+            let entry = self.entry_mut(bot);
+            entry.rating += delta;
+            match score {
+                s if s > 0.5 => entry.wins += 1,
+                s if s < 0.5 => entry.losses += 1,
+                _ => entry.draws += 1,
+            }
+        }
+    }
+
+    /// Entries from the best rating to the worst.
+    pub fn ranking(&self) -> Vec<&Entry> {
+        let mut entries: Vec<&Entry> = self.entries.iter().collect();
+        entries.sort_by(|a, b| b.rating.partial_cmp(&a.rating).unwrap());
+        entries
+    }
+}
+
+impl fmt::Display for Leaderboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>4}  {:<32}{:>7}{:>8}{:>7}{:>6}{:>6}{:>7}",
+            "rank", "bot", "elo", "+/-", "games", "wins", "draws", "losses"
+        )?;
+
+        for (i, e) in self.ranking().into_iter().enumerate() {
+            let margin = e
+                .error_margin()
+                .map_or_else(|| "inf".to_string(), |m| format!("{:.0}", m));
+            writeln!(
+                f,
+                "{:>4}  {:<32}{:>7.0}{:>8}{:>7}{:>6}{:>6}{:>7}",
+                i + 1,
+                e.bot,
+                e.rating,
+                margin,
+                e.games(),
+                e.wins,
+                e.draws,
+                e.losses
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Every bot meets every other one once.
+    RoundRobin,
+    /// Bots meet others with close scores, without rematches while possible.
+    Swiss { rounds: usize },
+}
+
+/// Games played between two bots, indexes are the ones of `Tournament::bots`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchResult {
+    pub a: usize,
+    pub b: usize,
+    pub wins_a: usize,
+    pub wins_b: usize,
+    pub draws: usize,
+}
+
+impl MatchResult {
+    /// Match points of `a`: 1 for a won match, 0.5 for a tie.
+    fn points_a(&self) -> f64 {
+        match self.wins_a.cmp(&self.wins_b) {
+            std::cmp::Ordering::Greater => 1.,
+            std::cmp::Ordering::Equal => 0.5,
+            std::cmp::Ordering::Less => 0.,
+        }
+    }
+}
+
+pub struct Tournament {
+    bots: Vec<BotConfig>,
+    format: Format,
+    games_per_match: usize,
+    new_arena: Box<dyn Fn() -> Arena>,
+}
+
+impl Tournament {
+    /// `new_arena` gives the board and rules of the matches, without players.
+    ///
+    /// Panics when a configuration is entered twice, both would share one leaderboard entry.
+    pub fn new(bots: Vec<BotConfig>, format: Format, new_arena: Box<dyn Fn() -> Arena>) -> Self {
+        if let Some(bot) = duplicate_bot(&bots) {
+            panic!("{} is entered twice", bot);
+        }
+        Tournament {
+            bots,
+            format,
+            games_per_match: 2,
+            new_arena,
+        }
+    }
+
+    pub fn with_games_per_match(mut self, games_per_match: usize) -> Self {
+        self.games_per_match = games_per_match;
+        self
+    }
+
+    #[inline]
+    pub fn bots(&self) -> &[BotConfig] {
+        &self.bots
+    }
+
+    /// Plays the tournament, recording every game in `leaderboard`. `on_match` is called after
+    /// each match.
+    pub fn run(&self, leaderboard: &mut Leaderboard, mut on_match: impl FnMut(&MatchResult)) {
+        match self.format {
+            Format::RoundRobin => {
+                for a in 0..self.bots.len() {
+                    for b in a + 1..self.bots.len() {
+                        on_match(&self.play_match(a, b, leaderboard));
+                    }
+                }
+            }
+            Format::Swiss { rounds } => {
+                let mut points = vec![0.; self.bots.len()];
+                let mut played = HashSet::new();
+                let mut byes = HashSet::new();
+
+                for _ in 0..rounds {
+                    let standings = self.standings(&points, leaderboard);
+                    let (pairs, bye) = swiss_pairings(&standings, &played, &byes);
+
+                    if let Some(bye) = bye {
+                        points[bye] += 1.;
+                        byes.insert(bye);
+                    }
+
+                    for (a, b) in pairs {
+                        let result = self.play_match(a, b, leaderboard);
+                        points[a] += result.points_a();
+                        points[b] += 1. - result.points_a();
+                        played.insert((a.min(b), a.max(b)));
+                        on_match(&result);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Bots from the most match points to the least, ratings break ties.
+    fn standings(&self, points: &[f64], leaderboard: &Leaderboard) -> Vec<usize> {
+        let ratings: Vec<f64> = self
+            .bots
+            .iter()
+            .map(|b| leaderboard.rating(&b.to_string()))
+            .collect();
+
+        let mut standings: Vec<usize> = (0..self.bots.len()).collect();
+        standings.sort_by(|&i, &j| {
+            (points[j], ratings[j])
+                .partial_cmp(&(points[i], ratings[i]))
+                .unwrap()
+        });
+        standings
+    }
+
+    fn play_match(&self, a: usize, b: usize, leaderboard: &mut Leaderboard) -> MatchResult {
+        let (name_a, name_b) = (self.bots[a].to_string(), self.bots[b].to_string());
+        let arena = (self.new_arena)()
+            .with_player(&name_a, self.bots[a].factory())
+            .with_player(&name_b, self.bots[b].factory());
+
+        let mut result = MatchResult {
+            a,
+            b,
+            wins_a: 0,
+            wins_b: 0,
+            draws: 0,
+        };
+        arena.run(self.games_per_match, |_, game| {
+            let score = match game.outcome {
+                Outcome::Win { id: 0 } => {
+                    result.wins_a += 1;
+                    1.
+                }
+                Outcome::Win { .. } => {
+                    result.wins_b += 1;
+                    0.
+                }
+                Outcome::Draw | Outcome::Ongoing => {
+                    result.draws += 1;
+                    0.5
+                }
+            };
+            leaderboard.record(&name_a, &name_b, score);
+        });
+
+        result
+    }
+}
+
+/// A configuration written like an earlier one, their games would be recorded as the same bot's.
+pub fn duplicate_bot(bots: &[BotConfig]) -> Option<String> {
+    let mut seen = HashSet::new();
+    bots.iter()
+        .map(|b| b.to_string())
+        .find(|b| !seen.insert(b.clone()))
+}
+
+/// Pairs bots of `standings` from the top, each one with the next bot it hasn't met yet or
+/// the next one when it met them all. With an odd number of bots, the lowest one that hasn't
+/// had one yet gets a bye.
+fn swiss_pairings(
+    standings: &[usize],
+    played: &HashSet<(usize, usize)>,
+    byes: &HashSet<usize>,
+) -> (Vec<(usize, usize)>, Option<usize>) {
+    let mut unpaired: Vec<usize> = standings.to_vec();
+
+    let bye = if unpaired.len() % 2 == 1 {
+        let i = unpaired
+            .iter()
+            .rposition(|b| !byes.contains(b))
+            .unwrap_or(unpaired.len() - 1);
+        Some(unpaired.remove(i))
+    } else {
+        None
+    };
+
+    let mut pairs = vec![];
+    while !unpaired.is_empty() {
+        let a = unpaired.remove(0);
+        let i = unpaired
+            .iter()
+            .position(|&b| !played.contains(&(a.min(b), a.max(b))))
+            .unwrap_or(0);
+        pairs.push((a, unpaired.remove(i)));
+    }
+
+    (pairs, bye)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ratings_move_towards_the_results() {
+        let mut leaderboard = Leaderboard::default();
+        leaderboard.record("a", "b", 1.);
+        leaderboard.record("a", "b", 0.5);
+
+        let (a, b) = (leaderboard.get("a").unwrap(), leaderboard.get("b").unwrap());
+        assert!(a.rating > INITIAL_RATING && b.rating < INITIAL_RATING);
+        assert!((a.rating + b.rating - 2. * INITIAL_RATING).abs() < 1e-9);
+        assert_eq!((a.wins, a.draws, a.losses), (1, 1, 0));
+        assert_eq!((b.wins, b.draws, b.losses), (0, 1, 1));
+
+        // a bot that never lost has no upper bound
+        assert_eq!(a.error_margin(), None);
+        assert!((expected_score(1600., 1500.) + expected_score(1500., 1600.) - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn error_margin_shrinks_with_games() {
+        let entry = |wins, draws, losses| Entry {
+            wins,
+            draws,
+            losses,
+            ..Entry::new("a")
+        };
+
+        let margin = entry(6, 2, 4).error_margin().unwrap();
+        assert!(entry(60, 20, 40).error_margin().unwrap() < margin);
+    }
+
+    #[test]
+    fn bots_entered_twice_are_found() {
+        let bots = |names: &[&str]| -> Vec<BotConfig> {
+            names.iter().map(|n| n.parse().unwrap()).collect()
+        };
+        assert_eq!(duplicate_bot(&bots(&["random", "bot_a"])), None);
+        assert_eq!(
+            duplicate_bot(&bots(&["random", "bot_a", "random"])),
+            Some("random".to_string())
+        );
+    }
+
+    #[test]
+    fn swiss_pairings_avoid_rematches() {
+        let played: HashSet<(usize, usize)> = vec![(0, 1)].into_iter().collect();
+        let (pairs, bye) = swiss_pairings(&[0, 1, 2, 3, 4], &played, &HashSet::new());
+
+        assert_eq!(pairs, vec![(0, 2), (1, 3)]);
+        assert_eq!(bye, Some(4));
+
+        let byes: HashSet<usize> = vec![4].into_iter().collect();
+        let (_, bye) = swiss_pairings(&[0, 1, 2, 3, 4], &played, &byes);
+        assert_eq!(bye, Some(3));
+    }
+}
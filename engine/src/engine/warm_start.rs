@@ -0,0 +1,202 @@
+//! Search statistics carried over from a turn to the next.
+//!
+//! The `mcts` crate can't move the root of a finished search down to one of its children, so
+//! the statistics below the child matching what the snakes played are kept instead, and given
+//! back to the next search as priors: a position met again starts with the visits and rewards
+//! it had, as if the search had carried on from there.
+//!
+//! Positions are told apart by their Zobrist hash, which counts health by tens: a position
+//! differing from a remembered one by a few points of health only gets its statistics too.
+//! These are a start the search corrects as it visits the position, and health changes little
+//! in the turn or two a search sees well.
+
+use std::collections::HashMap;
+
+use mcts::{transposition_table::TranspositionHash, GameState, NodeHandle, SearchTree};
+
+use crate::engine::{Movement, MyMCTS, SnakeGame};
+
+/// Moves visited fewer times are dropped, they tell little and make most of the tree.
+const MIN_VISITS: u64 = 8;
+
+/// Visits and rewards a move starts the search with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Prior {
+    pub visits: u64,
+    pub sum_rewards: i64,
+}
+
+#[derive(Clone, Debug)]
+struct MoveStats {
+    mov: Vec<Movement>,
+    prior: Prior,
+    child: Option<NodeStats>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct NodeStats {
+    moves: Vec<MoveStats>,
+}
+
+impl NodeStats {
+    /// Statistics below `node`, reached through the nodes of `path`. The transposition table
+    /// can lead a move back to one of them, such moves are kept without their child.
+    fn new(node: NodeHandle<MyMCTS>, path: &mut Vec<*const ()>) -> Self {
+        path.push(node.into_raw());
+        let moves = node
+            .moves()
+            .filter(|m| m.visits() >= MIN_VISITS)
+            .map(|m| MoveStats {
+                mov: m.get_move().clone(),
+                prior: Prior {
+                    visits: m.visits(),
+                    sum_rewards: m.sum_rewards(),
+                },
+                child: m
+                    .child()
+                    .filter(|child| !path.contains(&child.into_raw()))
+                    .map(|child| NodeStats::new(child, path)),
+            })
+            .collect();
+        path.pop();
+
+        NodeStats { moves }
+    }
+}
+
+/// Plays `mov` without spawning food, the positions being told apart by their snakes only.
+fn replay(game: &SnakeGame, mov: &[Movement]) -> SnakeGame {
+    let mut next = game.clone();
+    next.set_food_sampling(false);
+    next.make_move(&mov.to_vec());
+    next
+}
+
+/// The statistics of a finished search, from its root position.
+#[derive(Clone, Debug)]
+pub struct SearchMemory {
+    root: SnakeGame,
+    tree: NodeStats,
+}
+
+impl SearchMemory {
+    pub(crate) fn new(tree: &SearchTree<MyMCTS>) -> Self {
+        SearchMemory {
+            root: tree.root_state().clone(),
+            tree: NodeStats::new(tree.root_node(), &mut vec![]),
+        }
+    }
+
+    /// Priors of the search from `game`, empty unless `game` follows the remembered root by a
+    /// turn the search looked into.
+    pub fn priors(&self, game: &SnakeGame) -> Priors {
+        let hash = TranspositionHash::hash(game);
+        let mut priors = Priors::default();
+
+        for m in &self.tree.moves {
+            let next = replay(&self.root, &m.mov);
+            if TranspositionHash::hash(&next) == hash {
+                if let Some(child) = &m.child {
+                    priors.collect(next, child);
+                }
+                break;
+            }
+        }
+
+        priors
+    }
+}
+
+/// Move statistics of the positions a previous search went through.
+#[derive(Debug, Default)]
+pub struct Priors {
+    positions: HashMap<u64, Vec<(Vec<Movement>, Prior)>>,
+}
+
+impl Priors {
+    fn collect(&mut self, game: SnakeGame, node: &NodeStats) {
+        let moves = node
+            .moves
+            .iter()
+            .map(|m| (m.mov.clone(), m.prior))
+            .collect();
+        self.positions.insert(TranspositionHash::hash(&game), moves);
+
+        for m in &node.moves {
+            if let Some(child) = &m.child {
+                self.collect(replay(&game, &m.mov), child);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Priors of `moves` from `game`, zero for the ones the previous search didn't keep.
+    pub fn get(&self, game: &SnakeGame, moves: &[Vec<Movement>]) -> Vec<Prior> {
+        let known = if self.is_empty() {
+            None
+        } else {
+            self.positions.get(&TranspositionHash::hash(game))
+        };
+
+        moves
+            .iter()
+            .map(|mov| {
+                known
+                    .and_then(|known| known.iter().find(|(m, _)| m == mov))
+                    .map(|&(_, prior)| prior)
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::{Board, MyEvaluator, PriorUCTPolicy, Snake};
+    use crate::engine::{Point, DEFAULT_SNAKE_HEALTH};
+    use mcts::{transposition_table::ApproxTable, MCTSManager};
+
+    #[test]
+    fn priors_follow_the_played_move() {
+        let snakes = vec![
+            Snake::new_from(
+                DEFAULT_SNAKE_HEALTH,
+                vec![Point { x: 1, y: 1 }],
+                3,
+                Point { x: 1, y: 1 },
+            ),
+            Snake::new_from(
+                DEFAULT_SNAKE_HEALTH,
+                vec![Point { x: 5, y: 5 }],
+                3,
+                Point { x: 5, y: 5 },
+            ),
+        ];
+        let game = SnakeGame::new(Board::new_from(7, 7, snakes, &[], &[]));
+
+        let mut mcts = MCTSManager::new(
+            game.clone(),
+            MyMCTS,
+            MyEvaluator::default(),
+            PriorUCTPolicy::new(1.5),
+            ApproxTable::new(4096),
+        );
+        mcts.playout_n(2000);
+        let memory = SearchMemory::new(mcts.tree());
+
+        let played = mcts.best_move().unwrap();
+        let next = replay(&game, &played);
+        let priors = memory.priors(&next);
+        assert!(!priors.is_empty());
+
+        let moves = next.available_moves();
+        assert!(priors.get(&next, &moves).iter().any(|p| p.visits > 0));
+
+        // a position the search never reached
+        assert!(memory.priors(&replay(&next, &played)).is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+//! Zobrist keys of the parts of a position, XORed together into its hash.
+//!
+//! A turn changes a few parts of the board only, so its hash is kept up to date by XORing the
+//! keys of what changed out and in. The keys are mixed from what they stand for instead of
+//! drawn at random, every board and every run agree on them without a table to share.
+
+/// Snakes with health in the same tens hash the same, their positions share statistics.
+pub const HEALTH_BUCKET: i32 = 10;
+
+const BODY: u64 = 1;
+const HEAD: u64 = 2;
+const FOOD: u64 = 3;
+const HEALTH: u64 = 4;
+const PARITY: u64 = 5;
+const LENGTH: u64 = 6;
+
+/// The finalizer of splitmix64, spreading every bit of `z` over the whole key.
+#[inline]
+fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[inline]
+fn key(kind: u64, id: usize, value: usize) -> u64 {
+    mix(kind << 56 ^ (id as u64) << 32 ^ value as u64)
+}
+
+/// A part of the body of snake `id` on the cell of index `cell`, see `Layout::index`.
+#[inline]
+pub fn body(id: usize, cell: usize) -> u64 {
+    key(BODY, id, cell)
+}
+
+/// The head of snake `id` on the cell of index `cell`.
+#[inline]
+pub fn head(id: usize, cell: usize) -> u64 {
+    key(HEAD, id, cell)
+}
+
+#[inline]
+pub fn food(cell: usize) -> u64 {
+    key(FOOD, 0, cell)
+}
+
+#[inline]
+pub fn health(id: usize, health: i32) -> u64 {
+    key(HEALTH, id, (health / HEALTH_BUCKET) as usize)
+}
+
+/// The length of snake `id`, its body lags behind it after a meal.
+#[inline]
+pub fn length(id: usize, length: usize) -> u64 {
+    key(LENGTH, id, length)
+}
+
+/// Tells the odd turns from the even ones.
+#[inline]
+pub fn turn(turn: u32) -> u64 {
+    if turn % 2 == 1 {
+        key(PARITY, 0, 0)
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::engine::{Board, Movement, Point, Snake};
+
+    /// Two snakes of one cell, with the food in a corner out of their way.
+    fn board(heads: [(i32, i32); 2], health: i32, food: &[Point]) -> Board {
+        board_of_length(heads, health, 1, food)
+    }
+
+    fn board_of_length(
+        heads: [(i32, i32); 2],
+        health: i32,
+        length: usize,
+        food: &[Point],
+    ) -> Board {
+        let snakes = heads
+            .iter()
+            .map(|&(x, y)| Snake::new_from(health, vec![Point { x, y }], length, Point { x, y }))
+            .collect();
+        Board::new_from(5, 5, snakes, food, &[])
+    }
+
+    #[test]
+    fn transpositions_hash_the_same() {
+        let food = [Point { x: 0, y: 4 }];
+        let start = board([(1, 1), (4, 4)], 50, &food);
+        let play = |moves: [Movement; 2]| {
+            let mut played = start.clone();
+            moves
+                .iter()
+                .for_each(|&m| played.step(vec![m, Movement::Left], true));
+            played
+        };
+        let up_right = play([Movement::Up, Movement::Right]);
+        let right_up = play([Movement::Right, Movement::Up]);
+        assert_eq!(up_right.zobrist(), right_up.zobrist());
+
+        // kept up by the moves, the hash is the one of the position built from scratch
+        let hash = |heads, health, food: &[Point], turn| {
+            board(heads, health, food).with_turn(turn).zobrist()
+        };
+        let reached = hash([(2, 2), (2, 4)], 48, &food, 2);
+        assert_eq!(up_right.zobrist(), reached);
+
+        // health counts by tens only
+        assert_eq!(reached, hash([(2, 2), (2, 4)], 41, &food, 2));
+        assert_ne!(reached, hash([(2, 2), (2, 4)], 38, &food, 2));
+        assert_ne!(reached, hash([(2, 2), (2, 4)], 48, &food, 3));
+        assert_ne!(reached, hash([(2, 2), (2, 4)], 48, &[], 2));
+        assert_ne!(reached, hash([(2, 4), (2, 2)], 48, &food, 2));
+
+        // snakes of one cell about to grow
+        let longer = board_of_length([(2, 2), (2, 4)], 48, 2, &food).with_turn(2);
+        assert_ne!(reached, longer.zobrist());
+    }
+}
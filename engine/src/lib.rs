@@ -1,5 +1,16 @@
-#![feature(hash_drain_filter)]
-
 mod engine;
+#[cfg(feature = "tui")]
+pub mod renderer;
+
+pub use engine::{
+    api, ruleset_from_name, safe_move, Arena, ArenaStats, Board, BoardState, Collision, Death,
+    DeathCause, Frame, GameResult, Geometry, Movement, Outcome, Player, PlayerFactory, PlayerStats,
+    Point, RandomBot, Recorder, Replay, Ruleset, Settings, Snake, SnakeGame, Tile, Undo,
+    DEFAULT_SNAKE_HEALTH, STATE_FORMAT_VERSION,
+};
 
-pub use engine::{Board, Movement, Point, Snake, SnakeGame, BotA, Player};
+#[cfg(feature = "search")]
+pub use engine::{
+    duplicate_bot, expected_score, BotA, BotConfig, BotKind, Duct, DuctBot, Entry, Format,
+    Leaderboard, MatchResult, Paranoid, ParanoidBot, SearchMemory, Tournament,
+};
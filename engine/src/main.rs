@@ -1,14 +1,17 @@
-#![feature(hash_drain_filter)]
-mod engine;
 mod ui;
 
 extern crate piston_window;
 
-use crate::engine::{BotA, DEFAULT_SNAKE_HEALTH, Player, Board, Point, Snake, SnakeGame};
+use engine::{BotA, Board, Point, Recorder, Replay, Snake, SnakeGame, DEFAULT_SNAKE_HEALTH};
 use piston_window::color::hex;
 use piston_window::*;
 
 const COLOR_WALL: [f32; 4] = [0.8, 0.8, 0.7, 1.];
+const COLOR_HAZARD: [f32; 4] = [0.4, 0.1, 0.5, 0.6];
+// replays don't know the colors of the players
+const REPLAY_COLORS: [&str; 8] = [
+    "eeff11", "50E4EA", "57D658", "FC000A", "FF8C00", "C850FA", "FFFFFF", "2060FF",
+];
 
 const OFFSET: (f64, f64) = (100., 100.);
 const BOARD_WIDTH: usize = 9;
@@ -84,10 +87,17 @@ fn render_food(board: &Board, t: math::Matrix2d, gfx: &mut G2d) {
     });
 }
 
-fn render_players(board: &Board, players: &[Box<dyn Player>], t: math::Matrix2d, gfx: &mut G2d) {
+fn render_hazards(board: &Board, t: math::Matrix2d, gfx: &mut G2d) {
+    board.hazards().iter().for_each(|p| {
+        rectangle(COLOR_HAZARD, rectangle::square(x(p.x), y(p.y), TILE_SIZE), t, gfx);
+    });
+}
+
+/// `colors` has one entry per snake of the board.
+fn render_players(board: &Board, colors: &[[f32; 4]], t: math::Matrix2d, gfx: &mut G2d) {
     board.alive_snakes().for_each(|(id, s)| {
         let head = s.head();
-        let mut color = players[id].get_color();
+        let mut color = colors[id];
         let f = s.health() as f32 / DEFAULT_SNAKE_HEALTH as f32;
         color[3] = f;
 
@@ -110,7 +120,21 @@ fn render_players(board: &Board, players: &[Box<dyn Player>], t: math::Matrix2d,
 }
 
 fn main() {
-    let mut players: Vec<Box<dyn Player>> = vec![
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        [] => play(None),
+        ["record", path] => play(Some(path)),
+        ["replay", path] => replay(path),
+        _ => {
+            eprintln!("usage: engine [record <file> | replay <file>]");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Plays a live game, recorded to `record_path` when given.
+fn play(record_path: Option<&str>) {
+    let mut players: Vec<Box<dyn ui::UiPlayer>> = vec![
         Box::new(BotA::new(0, 12, color::hex("eeff11"))),
         // Box::new(ui::BotA::new(1, color::hex("eeff11"))),
         // Box::new(ui::BotA::new(3, color::hex("00ff11"))),
@@ -137,6 +161,10 @@ fn main() {
 
     let mut game = SnakeGame::new(board);
 
+    let mut recorder =
+        record_path.map(|path| Recorder::create(path, &game).expect("can't create the replay file"));
+    let colors: Vec<[f32; 4]> = players.iter().map(|p| p.get_color()).collect();
+
     players[0].think(&game);
 
     let mut window: PistonWindow = WindowSettings::new(
@@ -163,15 +191,22 @@ fn main() {
         window.draw_2d(&event, |context, graphics, _device| {
             clear([0.0; 4], graphics);
             render_walls(game.board(), context.transform, graphics);
+            render_hazards(game.board(), context.transform, graphics);
             render_food(game.board(), context.transform, graphics);
-            render_players(game.board(), &players, context.transform, graphics);
+            render_players(game.board(), &colors, context.transform, graphics);
         });
 
         event.update(|arg| {
             time += arg.dt;
 
             if time >= FREQ_SECONDS {
-                game.step(players.iter_mut().map(|p| p.next_move()).collect());
+                let moves = players.iter_mut().map(|p| p.next_move()).collect();
+                match recorder.as_mut() {
+                    Some(recorder) => recorder
+                        .step(&mut game, moves)
+                        .expect("can't write the replay file"),
+                    None => game.step(moves),
+                }
 
                 players.iter_mut().enumerate().for_each(|(id, p)| {
                     game.set_player(id);
@@ -182,8 +217,77 @@ fn main() {
             }
         });
 
-        if game.board().nb_snakes_alive() == 0 {
+        if game.board().is_game_over() {
             break;
         }
     }
 }
+
+/// Plays back a recorded game, see `ui::ReplayViewer` for the controls.
+fn replay(path: &str) {
+    let replay = Replay::load(path).expect("can't read the replay file");
+    let nb_snakes = replay.frames()[0].board.snakes().len();
+    let colors: Vec<[f32; 4]> = (0..nb_snakes)
+        .map(|id| hex(REPLAY_COLORS[id % REPLAY_COLORS.len()]))
+        .collect();
+
+    let (w, h) = (
+        replay.frames()[0].board.width(),
+        replay.frames()[0].board.height(),
+    );
+    let mut viewer = ui::ReplayViewer::new(replay, FREQ_SECONDS);
+
+    let mut window: PistonWindow = WindowSettings::new(
+        "Replay",
+        [
+            (w as f64 * TILE_SIZE + 2. * OFFSET.0) as u32,
+            (h as f64 * TILE_SIZE + 2. * OFFSET.1) as u32,
+        ],
+    )
+    .exit_on_esc(true)
+    .build()
+    .unwrap();
+
+    let mut shown = None;
+
+    while let Some(event) = window.next() {
+        if let Some(press_args) = event.press_args() {
+            viewer.register_key_event(press_args);
+        }
+
+        window.draw_2d(&event, |context, graphics, _device| {
+            clear([0.0; 4], graphics);
+            render_walls(viewer.board(), context.transform, graphics);
+            render_hazards(viewer.board(), context.transform, graphics);
+            render_food(viewer.board(), context.transform, graphics);
+            render_players(viewer.board(), &colors, context.transform, graphics);
+        });
+
+        event.update(|arg| viewer.update(arg.dt));
+
+        let state = (viewer.turn(), viewer.is_paused());
+        if shown != Some(state) {
+            // snakes killed by a collision during the turn
+            let crashed: Vec<String> = viewer
+                .frame()
+                .collisions
+                .iter()
+                .filter(|c| c.causes_death())
+                .map(|c| c.id().to_string())
+                .collect();
+            let crashed = match crashed.len() {
+                0 => String::new(),
+                _ => format!(" - crashed: {}", crashed.join(", ")),
+            };
+            let paused = if viewer.is_paused() { " (paused)" } else { "" };
+            window.set_title(format!(
+                "Replay - turn {}/{}{}{}",
+                viewer.turn(),
+                viewer.nb_turns(),
+                crashed,
+                paused
+            ));
+            shown = Some(state);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Terminal rendering of a `Board` with ruscii, to watch games without a window system.
+
+use ruscii::drawing::{Pencil, RectCharset};
+use ruscii::spatial::Vec2;
+use ruscii::terminal::{Color, Style};
+
+use crate::engine::Tile;
+use crate::Board;
+
+/// Colors of the snakes by id, red is kept for the food.
+pub const SNAKE_COLORS: [Color; 6] = [
+    Color::Yellow,
+    Color::Cyan,
+    Color::Green,
+    Color::Magenta,
+    Color::Xterm(208),
+    Color::Blue,
+];
+
+const COLOR_FOOD: Color = Color::Red;
+const COLOR_HAZARD: Color = Color::DarkGrey;
+const COLOR_BACKGROUND: Color = Color::Black;
+
+/// A cell of the board takes two characters so that it looks square.
+const CELL_WIDTH: i32 = 2;
+
+pub fn snake_color(id: usize) -> Color {
+    SNAKE_COLORS[id % SNAKE_COLORS.len()]
+}
+
+/// Size taken by `render_board`, walls included.
+pub fn board_dimension(board: &Board) -> Vec2 {
+    Vec2::xy(board.width() * CELL_WIDTH + 2, board.height() + 2)
+}
+
+/// Draws the board with its walls at the origin of the pencil, `y = 0` at the bottom like the
+/// official boards.
+pub fn render_board(pencil: &mut Pencil, board: &Board) {
+    pencil
+        .set_foreground(Color::LightGrey)
+        .set_background(COLOR_BACKGROUND)
+        .set_style(Style::Plain)
+        .draw_rect(
+            &RectCharset::simple_lines(),
+            Vec2::zero(),
+            board_dimension(board),
+        );
+
+    board.tiles().for_each(|(p, tile)| {
+        let position = Vec2::xy(1 + p.x * CELL_WIDTH, board.height() - p.y);
+        let background = if board.is_hazard(p) {
+            COLOR_HAZARD
+        } else {
+            COLOR_BACKGROUND
+        };
+
+        let (text, foreground, style) = match tile {
+            Tile::Empty => ("  ", Color::White, Style::Plain),
+            Tile::Food => ("()", COLOR_FOOD, Style::Bold),
+            Tile::Head { id } => ("@@", snake_color(id), Style::Bold),
+            Tile::Body { id } => ("██", snake_color(id), Style::Plain),
+        };
+
+        pencil
+            .set_background(background)
+            .set_foreground(foreground)
+            .set_style(style)
+            .draw_text(text, position);
+    });
+
+    pencil
+        .set_background(COLOR_BACKGROUND)
+        .set_style(Style::Plain);
+}
+
+/// Draws the turn and a line per snake with its health and length, dead snakes included.
+pub fn render_status(pencil: &mut Pencil, board: &Board) {
+    pencil
+        .set_foreground(Color::White)
+        .set_style(Style::Bold)
+        .draw_text(&format!("turn {}", board.turn()), Vec2::zero())
+        .set_style(Style::Plain);
+
+    board.snakes().iter().enumerate().for_each(|(id, s)| {
+        let status = if s.is_dead() {
+            "dead".to_string()
+        } else {
+            format!("health {:>3}  length {:>3}", s.health(), s.length())
+        };
+
+        pencil.set_foreground(snake_color(id)).draw_text(
+            &format!("snake {}  {}", id, status),
+            Vec2::xy(0, 2 + id as i32),
+        );
+    });
+}
@@ -1,5 +1,5 @@
-use crate::engine::{Movement, Player};
-use crate::SnakeGame;
+use super::UiPlayer;
+use engine::{Movement, Player, SnakeGame};
 use piston_window::Button;
 use piston_window::Key;
 
@@ -33,6 +33,10 @@ impl Player for Human {
         self.color
     }
 
+    fn think(&mut self, _: &SnakeGame) {}
+}
+
+impl UiPlayer for Human {
     fn register_key_event(&mut self, press_args: Button) {
         let mov = match press_args {
             Button::Keyboard(k) if k == self.keys[0] => Some(Movement::Left),
@@ -47,6 +51,4 @@ impl Player for Human {
             self.registered_mov = Some(mov);
         }
     }
-
-    fn think(&mut self, _: &SnakeGame) {}
 }
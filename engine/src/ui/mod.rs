@@ -1,3 +1,7 @@
 mod human;
+mod replay_viewer;
+mod ui_player;
 
 pub use human::*;
+pub use replay_viewer::*;
+pub use ui_player::*;
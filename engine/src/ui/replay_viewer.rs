@@ -0,0 +1,93 @@
+use engine::{Board, Frame, Replay};
+use piston_window::Button;
+use piston_window::Key;
+
+const MIN_PERIOD: f64 = 0.025;
+const MAX_PERIOD: f64 = 3.2;
+
+/// Plays a `Replay` back frame by frame.
+///
+/// Space pauses, Right and Left step forward and back, Home and End jump to the first and last
+/// turns, Up and Down speed the playback up or slow it down.
+pub struct ReplayViewer {
+    replay: Replay,
+    turn: usize,
+    paused: bool,
+    period: f64,
+    time: f64,
+}
+
+impl ReplayViewer {
+    pub(crate) fn new(replay: Replay, period: f64) -> Self {
+        Self {
+            replay,
+            turn: 0,
+            paused: false,
+            period,
+            time: 0.,
+        }
+    }
+
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    pub fn nb_turns(&self) -> usize {
+        self.replay.nb_turns()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn frame(&self) -> &Frame {
+        &self.replay.frames()[self.turn]
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.frame().board
+    }
+
+    pub fn register_key_event(&mut self, press_args: Button) {
+        let key = match press_args {
+            Button::Keyboard(k) => k,
+            _ => return,
+        };
+
+        match key {
+            Key::Space => self.paused = !self.paused,
+            Key::Right => {
+                self.paused = true;
+                self.turn = (self.turn + 1).min(self.nb_turns());
+            }
+            Key::Left => {
+                self.paused = true;
+                self.turn = self.turn.saturating_sub(1);
+            }
+            Key::Home => self.turn = 0,
+            Key::End => self.turn = self.nb_turns(),
+            Key::Up => self.period = (self.period / 2.).max(MIN_PERIOD),
+            Key::Down => self.period = (self.period * 2.).min(MAX_PERIOD),
+            _ => return,
+        }
+
+        self.time = 0.;
+    }
+
+    /// Moves to the next turn every `period` seconds, pausing at the end of the game.
+    pub fn update(&mut self, dt: f64) {
+        if self.paused {
+            return;
+        }
+
+        self.time += dt;
+        if self.time >= self.period {
+            self.time = 0.;
+            if self.turn < self.nb_turns() {
+                self.turn += 1;
+            } else {
+                self.paused = true;
+            }
+        }
+    }
+}
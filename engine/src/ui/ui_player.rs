@@ -0,0 +1,9 @@
+use engine::{BotA, Player};
+use piston_window::Button;
+
+/// A `Player` of the piston app, keyboard events of the window are sent to every player.
+pub trait UiPlayer: Player {
+    fn register_key_event(&mut self, _press_args: Button) {}
+}
+
+impl UiPlayer for BotA {}
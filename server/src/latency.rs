@@ -0,0 +1,118 @@
+//! Round trip times between the game engine and us, measured from the `/move` requests.
+//!
+//! The time between our answer to a turn and the request of the next turn covers the
+//! network both ways, plus the engine waiting for the other snakes. The smallest of these gaps
+//! is then an upper bound of the round trip time, and what has to be kept from the timeout to
+//! answer in time.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Margin before any round trip got measured.
+pub const DEFAULT_MARGIN: Duration = Duration::from_millis(100);
+/// Added to the measured round trip time for its variations.
+pub const JITTER_MARGIN: Duration = Duration::from_millis(10);
+/// Number of round trips kept.
+const WINDOW: usize = 16;
+
+/// The last round trips measured.
+#[derive(Debug, Default)]
+pub struct RoundTrips {
+    samples: VecDeque<Duration>,
+}
+
+impl RoundTrips {
+    pub fn push(&mut self, round_trip: Duration) {
+        if self.samples.len() == WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(round_trip);
+    }
+
+    pub fn min(&self) -> Option<Duration> {
+        self.samples.iter().min().copied()
+    }
+
+    /// Time to keep from the timeout for the answer to reach the engine, once a round trip got
+    /// measured.
+    pub fn margin(&self) -> Option<Duration> {
+        self.min().map(|round_trip| round_trip + JITTER_MARGIN)
+    }
+}
+
+/// Round trips of a game.
+#[derive(Debug, Default)]
+pub struct GameLatency {
+    turn: u32,
+    answered_at: Option<Instant>,
+    round_trips: RoundTrips,
+}
+
+impl GameLatency {
+    /// Records the `/move` request of `turn`, giving the round trip since the answer to the
+    /// previous turn.
+    pub fn request_received(&mut self, turn: u32, now: Instant) -> Option<Duration> {
+        let answered_at = self.answered_at.take();
+        let previous_turn = self.turn;
+        self.turn = turn;
+
+        let round_trip = answered_at
+            .filter(|_| turn == previous_turn + 1)
+            .map(|answered_at| now.saturating_duration_since(answered_at))?;
+        self.round_trips.push(round_trip);
+        Some(round_trip)
+    }
+
+    pub fn response_sent(&mut self, turn: u32, now: Instant) {
+        if self.turn == turn {
+            self.answered_at = Some(now);
+        }
+    }
+
+    pub fn round_trips(&self) -> &RoundTrips {
+        &self.round_trips
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn margin_from_round_trips() {
+        let mut latency = GameLatency::default();
+        let start = Instant::now();
+        let ms = |ms| start + Duration::from_millis(ms);
+
+        assert_eq!(latency.round_trips().margin(), None);
+
+        latency.request_received(0, ms(0));
+        latency.response_sent(0, ms(400));
+        latency.request_received(1, ms(480));
+        latency.response_sent(1, ms(900));
+        assert_eq!(
+            latency.request_received(2, ms(960)),
+            Some(Duration::from_millis(60))
+        );
+
+        assert_eq!(
+            latency.round_trips().margin(),
+            Some(Duration::from_millis(60) + JITTER_MARGIN)
+        );
+    }
+
+    #[test]
+    fn skipped_turns_are_not_measured() {
+        let mut latency = GameLatency::default();
+        let start = Instant::now();
+
+        latency.request_received(0, start);
+        latency.response_sent(0, start);
+        assert_eq!(
+            latency.request_received(5, start + Duration::from_secs(2)),
+            None
+        );
+
+        assert_eq!(latency.round_trips().margin(), None);
+    }
+}
@@ -2,10 +2,12 @@
 #![feature(available_concurrency)]
 
 // Modules
+mod latency;
 #[allow(dead_code)]
 mod requests;
 #[allow(dead_code)]
 mod responses;
+mod sessions;
 #[cfg(test)]
 mod test;
 
@@ -14,30 +16,20 @@ mod test;
 extern crate rocket;
 extern crate rocket_contrib;
 
+use std::convert::TryFrom;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
-use engine::Player;
-
 // Uses
 use rocket::http::Status;
+use rocket::State;
 use rocket_contrib::json::Json;
+use sessions::SessionStore;
 
-fn convert_snake(s: &requests::Snake) -> engine::Snake {
-    engine::Snake::new_from(s.health, s.body.clone(), s.length as usize, s.head)
-}
-
-fn convert_snakes(snakes: &[requests::Snake]) -> Vec<engine::Snake> {
-    snakes.into_iter().map(convert_snake).collect()
-}
-
-fn convert_board(board: &requests::Board) -> engine::Board {
-    engine::Board::new_from(
-        board.width,
-        board.height,
-        convert_snakes(&board.snakes),
-        &board.food,
-    )
-}
+/// Opponents further than this from our head get a single predicted move in the search.
+const PRUNING_DISTANCE: i32 = 6;
 
 #[get("/")]
 fn index() -> Json<responses::Info> {
@@ -51,59 +43,139 @@ fn index() -> Json<responses::Info> {
     })
 }
 
-#[post("/start")]
-fn start() -> Status {
+#[post("/start", data = "<req>")]
+fn start(req: Json<requests::Turn>, sessions: State<Mutex<SessionStore>>) -> Status {
+    sessions.lock().unwrap().start(&req, Instant::now());
     Status::Ok
 }
 
 #[post("/move", data = "<req>")]
-fn movement(req: Json<requests::Turn>) -> Json<responses::Move> {
-    let since_execution = Instant::now();
+fn movement(
+    req: Json<requests::Turn>,
+    sessions: State<Mutex<SessionStore>>,
+    config: State<engine::BotConfig>,
+) -> Json<responses::Move> {
+    let received_at = Instant::now();
+    let mut turn = req.into_inner();
+
+    // the engine may leave us out of the snakes, we still play from `you`
+    let snake_id = match turn.board.snakes.iter().position(|s| s.id == turn.you.id) {
+        Some(id) => id,
+        None => {
+            turn.board.snakes.push(turn.you.clone());
+            turn.board.snakes.len() - 1
+        }
+    };
+
+    // answer before the timeout, minus the time for the answer to reach the engine
+    let (margin, bot) = {
+        let mut sessions = sessions.lock().unwrap();
+        let margin = sessions.request_received(&turn, received_at);
+        (margin, sessions.take_bot(&turn, snake_id))
+    };
+    let timeout = Duration::from_millis(turn.game.timeout.max(0) as u64);
+    let deadline = received_at + timeout.saturating_sub(margin);
+
+    // whatever goes wrong with the game or the search, the engine gets a move
+    let (movement, bot) = panic::catch_unwind(AssertUnwindSafe(|| {
+        let board = match engine::Board::try_from(&turn) {
+            Ok(board) => board,
+            Err(e) => {
+                eprintln!("can't play game {}: {}", turn.game.id, e);
+                return (on_board_move(&turn), None);
+            }
+        };
+        let mut game = engine::SnakeGame::new(board);
+        game.set_player(snake_id);
+        game.set_food_sampling(true);
+        // bot A prunes the far opponents itself
+        if config.kind != engine::BotKind::BotA {
+            game.set_pruning_distance(Some(PRUNING_DISTANCE));
+        }
+
+        let mut bot = bot.unwrap_or_else(|| new_bot(&config, snake_id));
+        let movement = bot.search_until(&game, deadline);
+        (movement, Some((snake_id, bot)))
+    }))
+    .unwrap_or_else(|_| (on_board_move(&turn), None));
+
+    sessions
+        .lock()
+        .unwrap()
+        .response_sent(&turn, movement, bot, Instant::now());
+
+    Json(responses::Move::new(movement))
+}
 
-    let snake_id = req
-        .board
-        .snakes
-        .iter()
-        .enumerate()
-        .find(|(_, s)| s.id == req.you.id)
-        .map(|(id, _)| id)
-        .unwrap();
-
-    let latency_max = req.game.timeout as u64;
-    // fixme: replace by avg latency
-    let latency = req.you.latency as u64;
-
-    let board = convert_board(&req.board);
-    let mut game = engine::SnakeGame::new(board);
-    game.set_player(snake_id);
-
-    let mut bot = engine::BotA::new(
-        snake_id,
-        std::thread::available_concurrency()
+/// Bot playing snake `id` in a new game. Bot A searches with all the cores whatever its
+/// `threads`.
+fn new_bot(config: &engine::BotConfig, id: usize) -> Box<dyn engine::Player> {
+    let mut config = config.clone();
+    if config.kind == engine::BotKind::BotA {
+        config.threads = std::thread::available_concurrency()
             .map(|p| p.get())
-            .unwrap_or(32),
-        [0.0; 4],
-    );
-
-    bot.think(&game);
-
-    let sleep_time = latency_max - (latency + since_execution.elapsed().as_millis() as u64 + 10) ;
+            .unwrap_or(32);
+        config.pruning_distance = config.pruning_distance.or(Some(PRUNING_DISTANCE));
+    }
+    config.factory()(id)
+}
 
-    // fixme: that should be async + await, but who cares, 2h left lol
-    std::thread::sleep(Duration::from_millis(sleep_time));
+/// Last resort when the engine can't even read the turn: a move staying on the board, into a
+/// free cell when there is one.
+fn on_board_move(turn: &requests::Turn) -> engine::Movement {
+    let moves = [
+        engine::Movement::Down,
+        engine::Movement::Left,
+        engine::Movement::Up,
+        engine::Movement::Right,
+    ];
+    let board = &turn.board;
+    let on_board =
+        |p: engine::Point| p.x >= 0 && p.x < board.width && p.y >= 0 && p.y < board.height;
+    let free = |p: engine::Point| board.snakes.iter().all(|s| !s.body.contains(&p));
+
+    let head = turn.you.head;
+    moves
+        .iter()
+        .copied()
+        .find(|&m| on_board(head.apply_mov(m)) && free(head.apply_mov(m)))
+        .or_else(|| moves.iter().copied().find(|&m| on_board(head.apply_mov(m))))
+        .unwrap_or(engine::Movement::Up)
+}
 
-    let movement = responses::Move::new(bot.next_move());
+#[post("/end", data = "<req>")]
+fn end(req: Json<requests::Turn>, sessions: State<Mutex<SessionStore>>) -> Status {
+    let summary = sessions.lock().unwrap().end(&req, Instant::now());
+    if let Some(summary) = summary {
+        if let Err(e) = summary.append_to(summaries_path()) {
+            eprintln!("can't write the summary of game {}: {}", summary.game_id, e);
+        }
+    }
+    Status::Ok
+}
 
-    Json(movement)
+/// File the games are summed up in, a line each, `games.jsonl` unless the `GAME_SUMMARIES`
+/// variable names another one.
+fn summaries_path() -> PathBuf {
+    std::env::var_os("GAME_SUMMARIES").map_or_else(|| PathBuf::from("games.jsonl"), PathBuf::from)
 }
 
-#[post("/end")]
-fn end() -> Status {
-    Status::Ok
+/// Bot playing the games, `bot_a` unless the `BOT` variable names another one like `paranoid`,
+/// written as in `BotConfig`.
+fn bot_config() -> engine::BotConfig {
+    match std::env::var("BOT") {
+        Ok(bot) => bot
+            .parse()
+            .unwrap_or_else(|e| panic!("invalid BOT {}: {}", bot, e)),
+        Err(_) => engine::BotConfig::new(engine::BotKind::BotA),
+    }
 }
 
 fn rocket() -> rocket::Rocket {
-    rocket::ignite().mount("/", routes![index, start, movement, end])
+    rocket::ignite()
+        .manage(Mutex::new(SessionStore::new()))
+        .manage(bot_config())
+        .mount("/", routes![index, start, movement, end])
 }
 
 fn main() {
@@ -1,37 +1,34 @@
-use engine::Point;
-use serde::Deserialize;
-#[derive(Deserialize, PartialEq, Eq, Debug)]
-pub struct Turn {
-    pub game: Game,
-    pub turn: u32,
-    pub board: Board,
-    pub you: Snake,
-}
+pub use engine::api::Turn;
 
-#[derive(Deserialize, PartialEq, Eq, Debug)]
-pub struct Game {
-    pub id: String,
-    pub timeout: i32,
-}
+#[cfg(test)]
+mod test {
+    use engine::api::{Ruleset, Settings};
 
-#[derive(Deserialize, PartialEq, Eq, Debug)]
-pub struct Board {
-    pub height: i32,
-    pub width: i32,
-    pub food: Vec<Point>,
-    pub snakes: Vec<Snake>,
-    pub hazards: Vec<Point>,
-}
+    #[test]
+    fn deserialize_ruleset_settings() {
+        let string = r#"{
+            "name": "royale",
+            "version": "v1.2.3",
+            "settings": {
+                "foodSpawnChance": 25,
+                "minimumFood": 2,
+                "hazardDamagePerTurn": 16,
+                "royale": {"shrinkEveryNTurns": 10}
+            }
+        }"#;
+
+        let ruleset: Ruleset = serde_json::from_str(string).unwrap();
+        assert_eq!(ruleset.settings.food_spawn_chance, 25);
+        assert_eq!(ruleset.settings.minimum_food, 2);
+        assert_eq!(ruleset.settings.hazard_damage_per_turn, 16);
+        assert_eq!(ruleset.settings.royale.shrink_every_n_turns, 10);
+    }
+
+    #[test]
+    fn deserialize_ruleset_without_settings() {
+        let string = r#"{"name": "standard", "version": "v1.2.3"}"#;
 
-#[derive(Deserialize, PartialEq, Eq, Debug, Clone)]
-pub struct Snake {
-    pub id: String,
-    pub name: String,
-    pub health: i32,
-    pub body: Vec<Point>,
-    pub head: Point,
-    pub length: u32,
-    pub shout: String,
-    pub squad: String,
-    pub latency: i32,
+        let ruleset: Ruleset = serde_json::from_str(string).unwrap();
+        assert_eq!(ruleset.settings, Settings::default());
+    }
 }
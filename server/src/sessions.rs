@@ -0,0 +1,325 @@
+//! What the server keeps about each game it plays, from `/start` to `/end`.
+
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use engine::{Movement, Player};
+use serde::Serialize;
+
+use crate::latency::{GameLatency, RoundTrips, DEFAULT_MARGIN};
+use crate::requests::Turn;
+
+/// Games without requests for this long are forgotten.
+pub const SESSION_EXPIRY: Duration = Duration::from_secs(60);
+
+struct Session {
+    ruleset: String,
+    you: String,
+    opponents: Vec<String>,
+    started_at: Instant,
+    last_seen: Instant,
+    /// Our moves, turn by turn.
+    history: Vec<Movement>,
+    /// The bot playing the game and the index of its snake, kept with its search from a turn
+    /// to the next.
+    bot: Option<(usize, Box<dyn Player>)>,
+    latency: GameLatency,
+}
+
+impl Session {
+    fn new(turn: &Turn, now: Instant) -> Self {
+        Session {
+            ruleset: turn.game.ruleset.name.clone(),
+            you: turn.you.id.clone(),
+            opponents: turn
+                .board
+                .snakes
+                .iter()
+                .filter(|s| s.id != turn.you.id)
+                .map(|s| s.id.clone())
+                .collect(),
+            started_at: now,
+            last_seen: now,
+            history: vec![],
+            bot: None,
+            latency: GameLatency::default(),
+        }
+    }
+}
+
+/// Written out at the end of each game.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct GameSummary {
+    pub game_id: String,
+    pub ruleset: String,
+    pub you: String,
+    pub opponents: Vec<String>,
+    pub turns: u32,
+    /// The snake left alone on the board, none for a draw.
+    pub winner: Option<String>,
+    pub moves: Vec<Movement>,
+    pub duration_ms: u64,
+    /// Smallest round trip measured during the game.
+    pub round_trip_ms: Option<u64>,
+}
+
+impl GameSummary {
+    /// Appends the summary to the file at `path` as a line of JSON, creating the file.
+    pub fn append_to<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut line = serde_json::to_vec(self)?;
+        line.push(b'\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(&line)
+    }
+}
+
+/// A session per game and snake of ours, the server can play several snakes of a game.
+type SessionKey = (String, String);
+
+fn key(turn: &Turn) -> SessionKey {
+    (turn.game.id.clone(), turn.you.id.clone())
+}
+
+#[derive(Default)]
+pub struct SessionStore {
+    sessions: HashMap<SessionKey, Session>,
+    /// Round trips of all the games, used for the first turns of a game.
+    round_trips: RoundTrips,
+}
+
+impl SessionStore {
+    pub fn new() -> Self {
+        SessionStore::default()
+    }
+
+    fn expire(&mut self, now: Instant) {
+        self.sessions
+            .retain(|_, s| now.saturating_duration_since(s.last_seen) < SESSION_EXPIRY);
+    }
+
+    /// Session of the snake of `turn`, opened when the server missed its `/start`.
+    fn session(&mut self, turn: &Turn, now: Instant) -> &mut Session {
+        self.expire(now);
+        let session = self
+            .sessions
+            .entry(key(turn))
+            .or_insert_with(|| Session::new(turn, now));
+        session.last_seen = now;
+        session
+    }
+
+    pub fn start(&mut self, turn: &Turn, now: Instant) {
+        self.expire(now);
+        self.sessions.insert(key(turn), Session::new(turn, now));
+    }
+
+    /// Records the `/move` request of `turn`, giving the time to keep from its timeout for the
+    /// answer to reach the engine.
+    pub fn request_received(&mut self, turn: &Turn, now: Instant) -> Duration {
+        let session = self.session(turn, now);
+        let round_trip = session.latency.request_received(turn.turn, now);
+        let margin = session.latency.round_trips().margin();
+
+        if let Some(round_trip) = round_trip {
+            self.round_trips.push(round_trip);
+        }
+
+        margin
+            .or_else(|| self.round_trips.margin())
+            .unwrap_or(DEFAULT_MARGIN)
+    }
+
+    /// Bot of the snake of `turn`, if it played it at index `id` before: the engine leaves the
+    /// dead snakes out, the index of ours can change during the game.
+    pub fn take_bot(&mut self, turn: &Turn, id: usize) -> Option<Box<dyn Player>> {
+        match self.sessions.get_mut(&key(turn))?.bot.take() {
+            Some((bot_id, bot)) if bot_id == id => Some(bot),
+            _ => None,
+        }
+    }
+
+    pub fn response_sent(
+        &mut self,
+        turn: &Turn,
+        movement: Movement,
+        bot: Option<(usize, Box<dyn Player>)>,
+        now: Instant,
+    ) {
+        if let Some(session) = self.sessions.get_mut(&key(turn)) {
+            session.latency.response_sent(turn.turn, now);
+            session.history.push(movement);
+            session.bot = bot;
+        }
+    }
+
+    /// Closes the session of the snake of `turn`, the last one, and sums the game up.
+    pub fn end(&mut self, turn: &Turn, now: Instant) -> Option<GameSummary> {
+        self.expire(now);
+        let session = self.sessions.remove(&key(turn))?;
+
+        Some(GameSummary {
+            game_id: turn.game.id.clone(),
+            ruleset: session.ruleset,
+            you: session.you,
+            opponents: session.opponents,
+            turns: turn.turn,
+            winner: match turn.board.snakes.as_slice() {
+                [snake] => Some(snake.id.clone()),
+                _ => None,
+            },
+            moves: session.history,
+            duration_ms: now
+                .saturating_duration_since(session.started_at)
+                .as_millis() as u64,
+            round_trip_ms: session
+                .latency
+                .round_trips()
+                .min()
+                .map(|d| d.as_millis() as u64),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::latency::JITTER_MARGIN;
+    use engine::api::{Game, SnakeInfo};
+    use engine::{Board, Point, RandomBot, Snake};
+
+    fn turn(game_id: &str) -> Turn {
+        turn_of(game_id, 0)
+    }
+
+    /// Turn of a game between `a` and `b`, played as the snake of index `you`.
+    fn turn_of(game_id: &str, you: usize) -> Turn {
+        let snakes = vec![
+            Snake::new(Point { x: 1, y: 1 }),
+            Snake::new(Point { x: 5, y: 5 }),
+        ];
+        let board = Board::new(7, 7, snakes);
+        let snakes = [SnakeInfo::new("a"), SnakeInfo::new("b")];
+        Turn::from_engine(
+            &Game::from_engine(game_id, 500, &board),
+            &board,
+            &snakes,
+            you,
+        )
+    }
+
+    #[test]
+    fn session_sums_up_the_game() {
+        let mut sessions = SessionStore::new();
+        let start = Instant::now();
+        let ms = |ms| start + Duration::from_millis(ms);
+        let mut turn = turn("g");
+
+        sessions.start(&turn, ms(0));
+        assert_eq!(sessions.request_received(&turn, ms(0)), DEFAULT_MARGIN);
+        sessions.response_sent(&turn, Movement::Up, None, ms(400));
+
+        turn.turn = 1;
+        assert_eq!(
+            sessions.request_received(&turn, ms(460)),
+            Duration::from_millis(60) + JITTER_MARGIN
+        );
+        sessions.response_sent(&turn, Movement::Left, None, ms(900));
+
+        turn.turn = 2;
+        turn.board.snakes.retain(|s| s.id == "a");
+        let summary = sessions.end(&turn, ms(1000)).unwrap();
+        assert_eq!(summary.opponents, vec!["b".to_string()]);
+        assert_eq!(summary.turns, 2);
+        assert_eq!(summary.winner, Some("a".to_string()));
+        assert_eq!(summary.moves, vec![Movement::Up, Movement::Left]);
+        assert_eq!(summary.duration_ms, 1000);
+        assert_eq!(summary.round_trip_ms, Some(60));
+
+        assert!(sessions.end(&turn, ms(1000)).is_none());
+
+        let path = std::env::temp_dir().join(format!("summaries-{}.jsonl", std::process::id()));
+        summary.append_to(&path).unwrap();
+        summary.append_to(&path).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let line = serde_json::to_string(&summary).unwrap();
+        assert_eq!(written, format!("{}\n{}\n", line, line));
+    }
+
+    #[test]
+    fn stale_sessions_expire() {
+        let mut sessions = SessionStore::new();
+        let start = Instant::now();
+
+        sessions.start(&turn("g"), start);
+        sessions.start(&turn("h"), start + SESSION_EXPIRY);
+
+        assert!(sessions.end(&turn("g"), start + SESSION_EXPIRY).is_none());
+        assert!(sessions.end(&turn("h"), start + SESSION_EXPIRY).is_some());
+    }
+
+    #[test]
+    fn bots_are_handed_back_until_the_session_expires() {
+        let mut sessions = SessionStore::new();
+        let start = Instant::now();
+        let ms = |ms| start + Duration::from_millis(ms);
+        let bot = || -> Option<(usize, Box<dyn Player>)> {
+            Some((0, Box::new(RandomBot::new(0, [0.5; 4]))))
+        };
+
+        let turn = turn("g");
+
+        sessions.start(&turn, ms(0));
+        sessions.response_sent(&turn, Movement::Up, bot(), ms(400));
+        let handed_back = sessions.take_bot(&turn, 0).unwrap();
+        assert_eq!(handed_back.get_color(), [0.5; 4]);
+        assert!(sessions.take_bot(&turn, 0).is_none());
+
+        // a snake that died before ours moves it to another index
+        sessions.response_sent(&turn, Movement::Up, bot(), ms(900));
+        assert!(sessions.take_bot(&turn, 1).is_none());
+
+        sessions.response_sent(&turn, Movement::Up, bot(), ms(1400));
+        sessions.request_received(&turn_of("h", 0), ms(1400) + SESSION_EXPIRY);
+        assert!(sessions.take_bot(&turn, 0).is_none());
+    }
+
+    #[test]
+    fn snakes_of_a_game_have_sessions_of_their_own() {
+        let mut sessions = SessionStore::new();
+        let start = Instant::now();
+        let ms = |ms| start + Duration::from_millis(ms);
+        let (mut a, mut b) = (turn_of("g", 0), turn_of("g", 1));
+        let bot = |id| -> Option<(usize, Box<dyn Player>)> {
+            Some((id, Box::new(RandomBot::new(id, [0.5; 4]))))
+        };
+
+        sessions.start(&a, ms(0));
+        sessions.start(&b, ms(0));
+        for (turn, at) in (0..2).zip([0, 500].iter().copied()) {
+            a.turn = turn;
+            b.turn = turn;
+            sessions.request_received(&a, ms(at));
+            sessions.request_received(&b, ms(at + 10));
+            assert!(sessions.take_bot(&a, 0).is_some() == (turn > 0));
+            assert!(sessions.take_bot(&b, 1).is_some() == (turn > 0));
+            sessions.response_sent(&a, Movement::Up, bot(0), ms(at + 300));
+            sessions.response_sent(&b, Movement::Down, bot(1), ms(at + 310));
+        }
+
+        a.turn = 2;
+        b.turn = 2;
+        let (a, b) = (sessions.end(&a, ms(1000)), sessions.end(&b, ms(1000)));
+        let (a, b) = (a.unwrap(), b.unwrap());
+        assert_eq!((a.you.as_str(), a.moves), ("a", vec![Movement::Up; 2]));
+        assert_eq!((b.you.as_str(), b.moves), ("b", vec![Movement::Down; 2]));
+        assert_eq!(a.round_trip_ms, Some(200));
+        assert_eq!(b.round_trip_ms, Some(200));
+    }
+}